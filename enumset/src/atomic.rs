@@ -0,0 +1,255 @@
+use crate::repr::EnumSetTypeRepr;
+use crate::{EnumSet, EnumSetTypeWithRepr};
+use core::fmt;
+use core::fmt::{Debug, Formatter};
+use core::sync::atomic::Ordering;
+
+/// A trait marking representations that have a corresponding atomic integer type.
+///
+/// This is the bound [`AtomicEnumSet<T>`] places on the representation of `T`, and can be used to
+/// write code that is generic over the enums usable with it:
+///
+/// ```rust
+/// # use enumset::*;
+/// use std::sync::atomic::Ordering;
+///
+/// fn reset<T>(set: &AtomicEnumSet<T>) -> EnumSet<T>
+/// where
+///     T: EnumSetTypeWithRepr,
+///     <T as EnumSetTypeWithRepr>::Repr: EnumSetTypeAtomicRepr,
+/// {
+///     set.swap(EnumSet::empty(), Ordering::AcqRel)
+/// }
+/// ```
+///
+/// This trait is sealed, and cannot be implemented outside of this crate. It is implemented for
+/// `u8`, `u16`, `u32` and `u64` on targets that support atomic operations of that width.
+pub trait EnumSetTypeAtomicRepr: EnumSetTypeRepr {
+    /// The atomic integer type corresponding to this representation.
+    type Atomic: Send + Sync;
+
+    /// Creates a new atomic integer.
+    fn atomic_new(v: Self) -> Self::Atomic;
+    /// Consumes the atomic integer and returns the contained value.
+    fn atomic_into_inner(atomic: Self::Atomic) -> Self;
+    /// Loads the value of the atomic integer.
+    fn atomic_load(atomic: &Self::Atomic, order: Ordering) -> Self;
+    /// Stores a value into the atomic integer.
+    fn atomic_store(atomic: &Self::Atomic, v: Self, order: Ordering);
+    /// Stores a value into the atomic integer, returning the previous value.
+    fn atomic_swap(atomic: &Self::Atomic, v: Self, order: Ordering) -> Self;
+    /// Applies a bitwise or to the atomic integer, returning the previous value.
+    fn atomic_fetch_or(atomic: &Self::Atomic, v: Self, order: Ordering) -> Self;
+    /// Applies a bitwise and to the atomic integer, returning the previous value.
+    fn atomic_fetch_and(atomic: &Self::Atomic, v: Self, order: Ordering) -> Self;
+}
+macro_rules! atomic_prim {
+    ($name:ty, $atomic:ident, $width:literal) => {
+        #[cfg(target_has_atomic = $width)]
+        impl EnumSetTypeAtomicRepr for $name {
+            type Atomic = core::sync::atomic::$atomic;
+
+            #[inline(always)]
+            fn atomic_new(v: Self) -> Self::Atomic {
+                core::sync::atomic::$atomic::new(v)
+            }
+            #[inline(always)]
+            fn atomic_into_inner(atomic: Self::Atomic) -> Self {
+                atomic.into_inner()
+            }
+            #[inline(always)]
+            fn atomic_load(atomic: &Self::Atomic, order: Ordering) -> Self {
+                atomic.load(order)
+            }
+            #[inline(always)]
+            fn atomic_store(atomic: &Self::Atomic, v: Self, order: Ordering) {
+                atomic.store(v, order)
+            }
+            #[inline(always)]
+            fn atomic_swap(atomic: &Self::Atomic, v: Self, order: Ordering) -> Self {
+                atomic.swap(v, order)
+            }
+            #[inline(always)]
+            fn atomic_fetch_or(atomic: &Self::Atomic, v: Self, order: Ordering) -> Self {
+                atomic.fetch_or(v, order)
+            }
+            #[inline(always)]
+            fn atomic_fetch_and(atomic: &Self::Atomic, v: Self, order: Ordering) -> Self {
+                atomic.fetch_and(v, order)
+            }
+        }
+    };
+}
+atomic_prim!(u8, AtomicU8, "8");
+atomic_prim!(u16, AtomicU16, "16");
+atomic_prim!(u32, AtomicU32, "32");
+atomic_prim!(u64, AtomicU64, "64");
+
+/// The atomic integer type used to store an [`AtomicEnumSet<T>`].
+type AtomicReprOf<T> = <<T as EnumSetTypeWithRepr>::Repr as EnumSetTypeAtomicRepr>::Atomic;
+
+/// An [`EnumSet`] that can be safely shared between threads.
+///
+/// This type is backed by the atomic integer type corresponding to the representation of the
+/// set, and all operations on it are lock-free. As only bits corresponding to enum variants are
+/// ever set, the set loaded from it is always valid.
+///
+/// In order to use this type, the definition of `T` must have the `#[enumset(repr = "…")]`
+/// annotation, with a representation for which the target platform supports atomic operations.
+/// `u128` is not supported.
+///
+/// Each operation takes an [`Ordering`] describing the memory ordering of that operation, with
+/// the same meaning as for the corresponding operation on the standard atomic integer types
+/// such as [`AtomicU32`](core::sync::atomic::AtomicU32).
+///
+/// # Examples
+///
+/// ```rust
+/// # use enumset::*;
+/// use std::sync::atomic::Ordering;
+///
+/// #[derive(EnumSetType, Debug)]
+/// #[enumset(repr = "u32")]
+/// enum Flag { A, B, C }
+///
+/// let flags = AtomicEnumSet::new(Flag::A | Flag::B);
+/// assert!(flags.insert(Flag::C, Ordering::Relaxed));
+/// assert!(flags.remove(Flag::A, Ordering::Relaxed));
+/// assert_eq!(flags.load(Ordering::Relaxed), Flag::B | Flag::C);
+/// ```
+pub struct AtomicEnumSet<T: EnumSetTypeWithRepr>
+where <T as EnumSetTypeWithRepr>::Repr: EnumSetTypeAtomicRepr
+{
+    repr: AtomicReprOf<T>,
+}
+impl<T: EnumSetTypeWithRepr> AtomicEnumSet<T>
+where <T as EnumSetTypeWithRepr>::Repr: EnumSetTypeAtomicRepr
+{
+    /// Creates a new atomic set containing the elements of `set`.
+    #[inline(always)]
+    pub fn new(set: EnumSet<T>) -> Self {
+        AtomicEnumSet { repr: EnumSetTypeAtomicRepr::atomic_new(set.as_repr()) }
+    }
+
+    /// Consumes the atomic set and returns the contained set.
+    #[inline(always)]
+    pub fn into_inner(self) -> EnumSet<T> {
+        EnumSet { __priv_repr: EnumSetTypeAtomicRepr::atomic_into_inner(self.repr) }
+    }
+
+    /// Loads the current contents of the set.
+    ///
+    /// `order` describes the memory ordering of this load. Possible values are
+    /// [`Ordering::SeqCst`], [`Ordering::Acquire`] and [`Ordering::Relaxed`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `order` is [`Ordering::Release`] or [`Ordering::AcqRel`].
+    #[inline(always)]
+    pub fn load(&self, order: Ordering) -> EnumSet<T> {
+        EnumSet { __priv_repr: EnumSetTypeAtomicRepr::atomic_load(&self.repr, order) }
+    }
+
+    /// Replaces the contents of the set.
+    ///
+    /// `order` describes the memory ordering of this store. Possible values are
+    /// [`Ordering::SeqCst`], [`Ordering::Release`] and [`Ordering::Relaxed`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `order` is [`Ordering::Acquire`] or [`Ordering::AcqRel`].
+    #[inline(always)]
+    pub fn store(&self, set: EnumSet<T>, order: Ordering) {
+        EnumSetTypeAtomicRepr::atomic_store(&self.repr, set.as_repr(), order)
+    }
+
+    /// Replaces the contents of the set, returning the previous contents.
+    ///
+    /// `order` describes the memory ordering of this operation. All ordering modes are possible.
+    #[inline(always)]
+    pub fn swap(&self, set: EnumSet<T>, order: Ordering) -> EnumSet<T> {
+        let prev = EnumSetTypeAtomicRepr::atomic_swap(&self.repr, set.as_repr(), order);
+        EnumSet { __priv_repr: prev }
+    }
+
+    /// Checks whether this set currently contains a value.
+    ///
+    /// This loads the contents of the set, so `order` has the same meaning and restrictions as
+    /// for [`AtomicEnumSet::load`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `order` is [`Ordering::Release`] or [`Ordering::AcqRel`].
+    #[inline(always)]
+    pub fn contains(&self, value: T, order: Ordering) -> bool {
+        self.load(order).contains(value)
+    }
+
+    /// Adds a value to this set.
+    ///
+    /// If the set did not have this value present, `true` is returned.
+    ///
+    /// If the set did have this value present, `false` is returned.
+    ///
+    /// `order` describes the memory ordering of this operation. All ordering modes are possible.
+    #[inline(always)]
+    pub fn insert(&self, value: T, order: Ordering) -> bool {
+        let bit = EnumSet::only(value).as_repr();
+        let prev = EnumSetTypeAtomicRepr::atomic_fetch_or(&self.repr, bit, order);
+        !EnumSet::<T> { __priv_repr: prev }.contains(value)
+    }
+
+    /// Removes a value from this set. Returns whether the value was present in the set.
+    ///
+    /// `order` describes the memory ordering of this operation. All ordering modes are possible.
+    #[inline(always)]
+    pub fn remove(&self, value: T, order: Ordering) -> bool {
+        let mask = !EnumSet::only(value).as_repr();
+        let prev = EnumSetTypeAtomicRepr::atomic_fetch_and(&self.repr, mask, order);
+        EnumSet::<T> { __priv_repr: prev }.contains(value)
+    }
+
+    /// Adds all elements in another set to this one, returning the previous contents.
+    ///
+    /// `order` describes the memory ordering of this operation. All ordering modes are possible.
+    #[inline(always)]
+    pub fn insert_all(&self, other: EnumSet<T>, order: Ordering) -> EnumSet<T> {
+        let prev = EnumSetTypeAtomicRepr::atomic_fetch_or(&self.repr, other.as_repr(), order);
+        EnumSet { __priv_repr: prev }
+    }
+
+    /// Removes all values in another set from this one, returning the previous contents.
+    ///
+    /// `order` describes the memory ordering of this operation. All ordering modes are possible.
+    #[inline(always)]
+    pub fn remove_all(&self, other: EnumSet<T>, order: Ordering) -> EnumSet<T> {
+        let mask = !other.as_repr();
+        let prev = EnumSetTypeAtomicRepr::atomic_fetch_and(&self.repr, mask, order);
+        EnumSet { __priv_repr: prev }
+    }
+}
+
+impl<T: EnumSetTypeWithRepr> Default for AtomicEnumSet<T>
+where <T as EnumSetTypeWithRepr>::Repr: EnumSetTypeAtomicRepr
+{
+    /// Returns an empty set.
+    fn default() -> Self {
+        Self::new(EnumSet::new())
+    }
+}
+
+impl<T: EnumSetTypeWithRepr> From<EnumSet<T>> for AtomicEnumSet<T>
+where <T as EnumSetTypeWithRepr>::Repr: EnumSetTypeAtomicRepr
+{
+    fn from(set: EnumSet<T>) -> Self {
+        Self::new(set)
+    }
+}
+
+impl<T: EnumSetTypeWithRepr + Debug> Debug for AtomicEnumSet<T>
+where <T as EnumSetTypeWithRepr>::Repr: EnumSetTypeAtomicRepr
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&self.load(Ordering::Relaxed), f)
+    }
+}
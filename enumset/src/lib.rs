@@ -1,12 +1,22 @@
 #![no_std]
 #![forbid(missing_docs)]
+#![cfg_attr(feature = "nightly", feature(trusted_len))]
 // The safety requirement is "use the procedural derive".
 #![allow(clippy::missing_safety_doc)]
 
 //! A library for defining enums that can be used in compact bit sets. It supports enums up to 128
 //! variants, and has a macro to use these sets in constants.
 //!
-//! For serde support, enable the `serde` feature.
+//! For serde support, enable the `serde` feature. For support for randomly shuffling and sampling
+//! sets with the `rand` crate, enable the `rand` feature. For conversions to and from types
+//! generated by the `bitflags` crate, enable the `bitflags` feature. To collect sets into a
+//! `SmallVec` from the `smallvec` crate, enable the `smallvec` feature. To generate sets and enums
+//! with the `arbitrary` crate for fuzzing, enable the `arbitrary` feature. For a `proptest`
+//! strategy generating sets, enable the `proptest` feature. For `AtomicEnumSet`, a set that can be
+//! shared and updated between threads, enable the `atomic` feature.
+//!
+//! The `nightly` feature enables optimizations that depend on unstable Rust features, and
+//! requires a nightly compiler.
 //!
 //! # Defining enums for use with EnumSet
 //!
@@ -78,12 +88,21 @@
 //! assert_eq!(set, Enum::A | Enum::E | Enum::G);
 //! ```
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
 use core::cmp::Ordering;
+use core::convert::TryFrom;
 use core::fmt;
-use core::fmt::{Debug, Formatter};
+use core::fmt::{Debug, Display, Formatter};
 use core::hash::{Hash, Hasher};
-use core::iter::{FromIterator, Sum};
+use core::iter::{FromIterator, FusedIterator, Sum};
+#[cfg(any(feature = "rand", feature = "proptest"))]
+use core::marker::PhantomData;
 use core::ops::*;
+use core::str::FromStr;
 
 #[doc(hidden)]
 /// Everything in this module is internal API and may change at any time.
@@ -93,10 +112,17 @@ pub mod __internal {
     /// A reexport of core to allow our macros to be generic to std vs core.
     pub use ::core as core_export;
 
+    /// Used by the procedural derive to check that `EnumSet::single` is correct for an enum.
+    pub use crate::repr::const_enum_into_u32;
+
     /// A reexport of serde so there is no requirement to depend on serde.
     #[cfg(feature = "serde")]
     pub use serde2 as serde;
 
+    /// A reexport of arbitrary so there is no requirement to depend on arbitrary.
+    #[cfg(feature = "arbitrary")]
+    pub use arbitrary1 as arbitrary;
+
     /// The actual members of EnumSetType. Put here to avoid polluting global namespaces.
     pub unsafe trait EnumSetTypePrivate {
         /// The underlying type used to store the bitset.
@@ -108,6 +134,8 @@ pub mod __internal {
         fn enum_into_u32(self) -> u32;
         /// Converts a bit position into an enum value.
         unsafe fn enum_from_u32(val: u32) -> Self;
+        /// Returns the name of the variant, as written in the enum definition.
+        fn enum_name(self) -> &'static str;
 
         /// Serializes the `EnumSet`.
         ///
@@ -131,6 +159,11 @@ use crate::serde::{Deserialize, Serialize};
 mod repr;
 use crate::repr::EnumSetTypeRepr;
 
+#[cfg(feature = "atomic")]
+mod atomic;
+#[cfg(feature = "atomic")]
+pub use crate::atomic::{AtomicEnumSet, EnumSetTypeAtomicRepr};
+
 /// The procedural macro used to derive [`EnumSetType`], and allow enums to be used with
 /// [`EnumSet`].
 ///
@@ -151,6 +184,8 @@ use crate::repr::EnumSetTypeRepr;
 /// * [`PartialEq`], [`Sub`], [`BitAnd`], [`BitOr`], [`BitXor`], and [`Not`] implementations are
 ///   created to allow the crate to be used more ergonomically in expressions. These automatic
 ///   implementations may be suppressed using `#[enumset(no_ops)]`.
+/// * When the `arbitrary` feature is enabled, an implementation of `arbitrary::Arbitrary` is
+///   created that picks one of the variants of the enum.
 ///
 /// # Options
 ///
@@ -173,6 +208,10 @@ use crate::repr::EnumSetTypeRepr;
 ///   “FFI, Safety and `repr`”][EnumSet#ffi-safety-and-repr]. Allowed types are `u8`, `u16`, `u32`,
 ///   `u64` and `u128`. If this is not used, then the derive macro will choose a type to best fit
 ///   the enum, but there are no guarantees about which type will be chosen.
+/// * `#[enumset(subset_of = "SuperEnum")]` declares that every variant of this enum also exists
+///   in `SuperEnum` with the same discriminant, and implements [`EnumSetSubsetOf<SuperEnum>`]
+///   for this enum. This is checked at compile time. It allows sets of `SuperEnum` to be
+///   narrowed into sets of this enum using [`EnumSet::narrow`].
 ///
 /// When the `serde` feature is used, the following features may also be specified. These options
 /// may be used (with no effect) when building without the feature enabled:
@@ -182,8 +221,12 @@ use crate::repr::EnumSetTypeRepr;
 /// * `#[enumset(serialize_as_list)]` may be used to serialize the bitset as a list of enum
 ///   variants instead of an integer. This requires [`Deserialize`] and [`Serialize`] be
 ///   implemented on the enum.
+/// * `#[enumset(serialize_as_map)]` may be used to serialize the bitset as a map from the name of
+///   every variant to whether it is present in the set.
 /// * `#[enumset(serialize_deny_unknown)]` causes the generated deserializer to return an error
 ///   for unknown bits instead of silently ignoring them.
+/// * `#[enumset(serialize_versioned)]` causes the bitset to be serialized together with a format
+///   version, allowing future changes to the format to be detected.
 ///
 /// # Examples
 ///
@@ -244,6 +287,19 @@ pub unsafe trait EnumSetTypeWithRepr:
     type Repr: EnumSetTypeRepr;
 }
 
+/// An [`EnumSetType`] whose variants are all variants of another enum `Super`, with the same
+/// discriminants.
+///
+/// An implementation of this trait is generated by using
+/// [`#[derive(EnumSetType)]`](./derive.EnumSetType.html) with the annotation
+/// `#[enumset(subset_of = "Super")]`. It allows an `EnumSet<Super>` to be converted into an
+/// `EnumSet<Self>` using [`EnumSet::narrow`].
+///
+/// This trait may also be implemented manually, in which case it is the implementer's
+/// responsibility to ensure the discriminants match. Conversions are safe regardless, but may
+/// produce unexpected results otherwise.
+pub trait EnumSetSubsetOf<Super: EnumSetType>: EnumSetType {}
+
 /// An efficient set type for enums.
 ///
 /// It is implemented using a bitset stored using the smallest integer that can fit all bits
@@ -284,6 +340,18 @@ pub unsafe trait EnumSetTypeWithRepr:
 /// instead serialized as a list of enum variants. This requires your enum type implement
 /// [`Serialize`] and [`Deserialize`]. Note that this is a breaking change.
 ///
+/// The `#[enumset(serialize_as_map)]` attribute causes the `EnumSet` to be serialized as a map
+/// from the name of each variant to a boolean, e.g. `{"A": true, "B": false}`. Every variant is
+/// included when serializing, while variants missing from the map are treated as absent when
+/// deserializing. Unknown keys are ignored, unless `#[enumset(serialize_deny_unknown)]` is also
+/// used. Unlike `serialize_as_list`, this does not require your enum type to implement
+/// [`Serialize`] or [`Deserialize`].
+///
+/// The `#[enumset(serialize_versioned)]` attribute causes the `EnumSet` to be serialized as a
+/// tuple of a `u8` format version and the bitset integer. The only version currently defined is
+/// `1`, and deserializing any other version will fail. Only one of `serialize_as_list`,
+/// `serialize_as_map` and `serialize_versioned` may be used.
+///
 /// # FFI, Safety and `repr`
 ///
 /// If an enum type `T` is annotated with [`#[enumset(repr = "R")]`][derive@EnumSetType#options],
@@ -293,7 +361,9 @@ pub unsafe trait EnumSetTypeWithRepr:
 ///   [`EnumSetType`].
 /// * The `EnumSet` methods with `repr` in their name, such as [`as_repr`][EnumSet::as_repr] and
 ///   [`from_repr`][EnumSet::from_repr], will be available for `EnumSet<T>`.
-/// * The in-memory representation of `EnumSet<T>` is guaranteed to be `R`.
+/// * The in-memory representation of `EnumSet<T>` is guaranteed to be `R`. In particular,
+///   `EnumSet<T>` has the same size and alignment as `R`, which are available as
+///   [`repr_size`][EnumSet::repr_size] and [`repr_align`][EnumSet::repr_align].
 ///
 /// That last guarantee makes it sound to send `EnumSet<T>` across an FFI boundary. For example:
 ///
@@ -356,6 +426,22 @@ impl<T: EnumSetType> EnumSet<T> {
         set
     }
 
+    /// Returns an `EnumSet` containing a single element.
+    ///
+    /// Unlike [`EnumSet::only`], this method may be used in `const` contexts:
+    ///
+    /// ```rust
+    /// # use enumset::*;
+    /// # #[derive(EnumSetType, Debug)] pub enum Enum { A, B, C }
+    /// const SET: EnumSet<Enum> = EnumSet::single(Enum::B);
+    /// assert_eq!(SET, Enum::B);
+    /// ```
+    #[inline(always)]
+    pub const fn single(value: T) -> Self {
+        let bit = repr::const_enum_into_u32(value);
+        EnumSet { __priv_repr: repr::const_repr_from_u128(1 << bit) }
+    }
+
     /// Creates an empty `EnumSet`.
     ///
     /// This is an alias for [`EnumSet::new`].
@@ -364,12 +450,79 @@ impl<T: EnumSetType> EnumSet<T> {
         Self::new()
     }
 
+    /// Creates an `EnumSet` from the values of an iterator, validating each value before it is
+    /// inserted.
+    ///
+    /// If `validate` returns an error for any value, iteration stops and that error is returned.
+    /// The partially constructed set is discarded.
+    pub fn try_from_iter<I, E, F>(iter: I, mut validate: F) -> Result<Self, E>
+    where
+        I: IntoIterator<Item = T>,
+        F: FnMut(&T) -> Result<(), E>,
+    {
+        let mut set = Self::new();
+        for value in iter {
+            validate(&value)?;
+            set.insert(value);
+        }
+        Ok(set)
+    }
+
     /// Returns an `EnumSet` containing all valid variants of the enum.
     #[inline(always)]
     pub fn all() -> Self {
         EnumSet { __priv_repr: Self::all_bits() }
     }
 
+    /// Returns the intersection of all sets produced by an iterator.
+    ///
+    /// This is the counterpart of the [`Sum`] implementation, which returns their union. If the
+    /// iterator is empty, [`EnumSet::all`] is returned.
+    pub fn intersect_all<I: IntoIterator<Item = Self>>(iter: I) -> Self {
+        iter.into_iter().fold(Self::all(), |a, v| a & v)
+    }
+
+    /// Returns an `EnumSet` containing all valid variants of the enum, except those in `excluded`.
+    ///
+    /// This is equivalent to `EnumSet::all() - excluded`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use enumset::*;
+    /// #[derive(EnumSetType, Debug)]
+    /// enum Feature { Logging, Caching, Experimental, Debug }
+    ///
+    /// let defaults = EnumSet::all_except(Feature::Experimental | Feature::Debug);
+    /// assert_eq!(defaults, Feature::Logging | Feature::Caching);
+    /// ```
+    #[inline(always)]
+    pub fn all_except(excluded: Self) -> Self {
+        Self::all().difference(excluded)
+    }
+
+    /// Returns an `EnumSet` containing all valid variants of the enum, except those in `excluded`.
+    ///
+    /// This is a version of [`EnumSet::all_except`] that can be used in `const` contexts.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use enumset::*;
+    /// #[derive(EnumSetType, Debug)]
+    /// enum Feature { Logging, Caching, Experimental, Debug }
+    ///
+    /// const DEFAULTS: EnumSet<Feature> =
+    ///     EnumSet::all_except_const(enum_set!(Feature::Experimental | Feature::Debug));
+    /// assert_eq!(DEFAULTS, Feature::Logging | Feature::Caching);
+    /// ```
+    #[inline(always)]
+    pub const fn all_except_const(excluded: Self) -> Self {
+        let all = repr::const_repr_to_u128(T::ALL_BITS);
+        let excluded = repr::const_repr_to_u128(excluded.__priv_repr);
+        EnumSet { __priv_repr: repr::const_repr_from_u128(all & !excluded) }
+    }
+
     /// Total number of bits used by this type. Note that the actual amount of space used is
     /// rounded up to the next highest integer type (`u8`, `u16`, `u32`, `u64`, or `u128`).
     ///
@@ -391,13 +544,42 @@ impl<T: EnumSetType> EnumSet<T> {
 
     /// Returns the number of elements in this set.
     #[inline(always)]
-    pub fn len(&self) -> usize {
-        self.__priv_repr.count_ones() as usize
+    pub const fn len(&self) -> usize {
+        repr::const_repr_to_u128(self.__priv_repr).count_ones() as usize
+    }
+    /// Returns the width of the smallest integer type (`u8`, `u16`, `u32`, `u64`, or `u128`) that
+    /// can hold the elements of this particular set.
+    ///
+    /// Unlike [`EnumSet::bit_width`], which depends only on the enum type, this depends on the
+    /// highest element present in this set. This is useful for choosing a compact encoding for
+    /// each value when most sets only contain low variants. An empty set returns `8`.
+    #[inline(always)]
+    pub fn minimal_repr_width(&self) -> u32 {
+        match T::Repr::WIDTH - self.__priv_repr.leading_zeros() {
+            0..=8 => 8,
+            9..=16 => 16,
+            17..=32 => 32,
+            33..=64 => 64,
+            _ => 128,
+        }
+    }
+    /// Returns the number of elements of this set contained in each of several masks.
+    ///
+    /// This is useful for counting elements in several categories at once, where each category
+    /// is represented by a set. Note that elements present in more than one mask are counted once
+    /// for each mask.
+    #[inline(always)]
+    pub fn counts_by_masks<const N: usize>(&self, masks: [Self; N]) -> [usize; N] {
+        let mut counts = [0; N];
+        for (count, mask) in counts.iter_mut().zip(masks.iter()) {
+            *count = self.intersection(*mask).len();
+        }
+        counts
     }
     /// Returns `true` if the set contains no elements.
     #[inline(always)]
-    pub fn is_empty(&self) -> bool {
-        self.__priv_repr.is_empty()
+    pub const fn is_empty(&self) -> bool {
+        repr::const_repr_to_u128(self.__priv_repr) == 0
     }
     /// Removes all elements from the set.
     #[inline(always)]
@@ -405,6 +587,28 @@ impl<T: EnumSetType> EnumSet<T> {
         self.__priv_repr = T::Repr::empty()
     }
 
+    /// Returns `true` if the elements of this set have consecutive discriminants, with no gaps
+    /// between them.
+    ///
+    /// The empty set is considered to be contiguous.
+    #[inline(always)]
+    pub fn is_contiguous(&self) -> bool {
+        if self.is_empty() {
+            true
+        } else {
+            let bits = self.__priv_repr;
+            let span = T::Repr::WIDTH - bits.leading_zeros() - bits.trailing_zeros();
+            bits.count_ones() == span
+        }
+    }
+    /// Returns `true` if this set contains the variant with a discriminant of `0`.
+    ///
+    /// This is always `false` for enums with no such variant.
+    #[inline(always)]
+    pub fn starts_at_zero(&self) -> bool {
+        self.__priv_repr.has_bit(0)
+    }
+
     /// Returns `true` if `self` has no elements in common with `other`. This is equivalent to
     /// checking for an empty intersection.
     #[inline(always)]
@@ -423,187 +627,1441 @@ impl<T: EnumSetType> EnumSet<T> {
     pub fn is_subset(&self, other: Self) -> bool {
         other.is_superset(*self)
     }
+    /// Returns `true` if the set is a superset of another and not equal to it, i.e., `self`
+    /// contains all the values in `other` and at least one value that is not in `other`.
+    #[inline(always)]
+    pub fn is_proper_superset(&self, other: Self) -> bool {
+        self.is_superset(other) && *self != other
+    }
+    /// Returns `true` if the set is a subset of another and not equal to it, i.e., `other`
+    /// contains all the values in `self` and at least one value that is not in `self`.
+    #[inline(always)]
+    pub fn is_proper_subset(&self, other: Self) -> bool {
+        self.is_subset(other) && *self != other
+    }
+    /// Returns `true` if `self` contains any values that are not in `other`. This is equivalent to
+    /// checking for a non-empty difference, or to `!self.is_subset(other)`.
+    #[inline(always)]
+    pub fn has_extra_beyond(&self, other: Self) -> bool {
+        !self.difference(other).is_empty()
+    }
+    /// Checks that the set is a subset of `allowed`, returning the values not in `allowed` as an
+    /// error otherwise.
+    ///
+    /// This is useful for permission checks, where the error reports exactly which of the
+    /// requested values are not permitted.
+    #[inline(always)]
+    pub fn require_subset_of(&self, allowed: Self) -> Result<(), Self> {
+        let extra = self.difference(allowed);
+        if extra.is_empty() {
+            Ok(())
+        } else {
+            Err(extra)
+        }
+    }
+
+    /// Returns the number of elements in the union of `self` and `other`.
+    ///
+    /// This is equivalent to `(self | other).len()`.
+    #[inline(always)]
+    pub fn union_len(&self, other: Self) -> usize {
+        self.union(other).len()
+    }
+    /// Returns the number of elements present in both `self` and `other`.
+    ///
+    /// This is equivalent to `(self & other).len()`.
+    #[inline(always)]
+    pub fn intersection_count(&self, other: Self) -> usize {
+        self.intersection(other).len()
+    }
+    /// Returns the number of elements present in `self` but not in `other`.
+    ///
+    /// This is equivalent to `(self - other).len()`.
+    #[inline(always)]
+    pub fn difference_count(&self, other: Self) -> usize {
+        self.difference(other).len()
+    }
+    /// Returns the number of elements present in either `self` or `other`, but not in both.
+    ///
+    /// This is equivalent to `(self ^ other).len()`.
+    #[inline(always)]
+    pub fn symmetric_difference_count(&self, other: Self) -> usize {
+        self.symmetrical_difference(other).len()
+    }
 
     /// Returns a set containing any elements present in either set.
+    ///
+    /// This method, like the other set operations, can be used in `const` contexts:
+    ///
+    /// ```rust
+    /// # use enumset::*;
+    /// #[derive(EnumSetType, Debug)]
+    /// enum Enum { A, B, C, D }
+    ///
+    /// const BASE: EnumSet<Enum> = enum_set!(Enum::A | Enum::B);
+    /// const EXTRA: EnumSet<Enum> = enum_set!(Enum::D);
+    /// const DERIVED: EnumSet<Enum> = BASE.union(EXTRA);
+    /// assert_eq!(DERIVED, Enum::A | Enum::B | Enum::D);
+    /// ```
     #[inline(always)]
-    pub fn union(&self, other: Self) -> Self {
-        EnumSet { __priv_repr: self.__priv_repr | other.__priv_repr }
+    pub const fn union(&self, other: Self) -> Self {
+        self.const_union(other)
     }
     /// Returns a set containing every element present in both sets.
     #[inline(always)]
-    pub fn intersection(&self, other: Self) -> Self {
-        EnumSet { __priv_repr: self.__priv_repr & other.__priv_repr }
+    pub const fn intersection(&self, other: Self) -> Self {
+        self.const_intersection(other)
     }
     /// Returns a set containing element present in `self` but not in `other`.
     #[inline(always)]
-    pub fn difference(&self, other: Self) -> Self {
-        EnumSet { __priv_repr: self.__priv_repr.and_not(other.__priv_repr) }
+    pub const fn difference(&self, other: Self) -> Self {
+        self.const_difference(other)
     }
     /// Returns a set containing every element present in either `self` or `other`, but is not
     /// present in both.
     #[inline(always)]
-    pub fn symmetrical_difference(&self, other: Self) -> Self {
-        EnumSet { __priv_repr: self.__priv_repr ^ other.__priv_repr }
+    pub const fn symmetrical_difference(&self, other: Self) -> Self {
+        let bits = repr::const_repr_to_u128(self.__priv_repr)
+            ^ repr::const_repr_to_u128(other.__priv_repr);
+        EnumSet { __priv_repr: repr::const_repr_from_u128(bits) }
     }
     /// Returns a set containing all enum variants not in this set.
     #[inline(always)]
-    pub fn complement(&self) -> Self {
-        EnumSet { __priv_repr: !self.__priv_repr & Self::all_bits() }
+    pub const fn complement(&self) -> Self {
+        self.const_complement()
     }
 
-    /// Checks whether this set contains a value.
+    /// Returns a set containing any elements present in either set.
+    ///
+    /// This is equivalent to [`EnumSet::union`], but takes `self` by value.
     #[inline(always)]
-    pub fn contains(&self, value: T) -> bool {
-        self.__priv_repr.has_bit(value.enum_into_u32())
+    pub const fn const_union(self, other: Self) -> Self {
+        let bits = repr::const_repr_to_u128(self.__priv_repr)
+            | repr::const_repr_to_u128(other.__priv_repr);
+        EnumSet { __priv_repr: repr::const_repr_from_u128(bits) }
     }
-
-    /// Adds a value to this set.
+    /// Returns a set containing every element present in both sets.
     ///
-    /// If the set did not have this value present, `true` is returned.
+    /// This is equivalent to [`EnumSet::intersection`], but takes `self` by value.
+    #[inline(always)]
+    pub const fn const_intersection(self, other: Self) -> Self {
+        let bits = repr::const_repr_to_u128(self.__priv_repr)
+            & repr::const_repr_to_u128(other.__priv_repr);
+        EnumSet { __priv_repr: repr::const_repr_from_u128(bits) }
+    }
+    /// Returns a set containing element present in `self` but not in `other`.
     ///
-    /// If the set did have this value present, `false` is returned.
+    /// This is equivalent to [`EnumSet::difference`], but takes `self` by value.
     #[inline(always)]
-    pub fn insert(&mut self, value: T) -> bool {
-        let contains = !self.contains(value);
-        self.__priv_repr.add_bit(value.enum_into_u32());
-        contains
+    pub const fn const_difference(self, other: Self) -> Self {
+        let bits = repr::const_repr_to_u128(self.__priv_repr)
+            & !repr::const_repr_to_u128(other.__priv_repr);
+        EnumSet { __priv_repr: repr::const_repr_from_u128(bits) }
     }
-    /// Removes a value from this set. Returns whether the value was present in the set.
+    /// Returns a set containing all enum variants not in this set.
+    ///
+    /// This is equivalent to [`EnumSet::complement`], but takes `self` by value.
     #[inline(always)]
-    pub fn remove(&mut self, value: T) -> bool {
-        let contains = self.contains(value);
-        self.__priv_repr.remove_bit(value.enum_into_u32());
-        contains
+    pub const fn const_complement(self) -> Self {
+        Self::all_except_const(self)
     }
 
-    /// Adds all elements in another set to this one.
+    /// Applies a set operation selected at runtime to this set and `other`.
+    ///
+    /// This dispatches to [`EnumSet::union`], [`EnumSet::intersection`],
+    /// [`EnumSet::difference`] or [`EnumSet::symmetrical_difference`] depending on `op`.
     #[inline(always)]
-    pub fn insert_all(&mut self, other: Self) {
-        self.__priv_repr = self.__priv_repr | other.__priv_repr
+    pub fn apply_op(&self, op: SetOp, other: Self) -> Self {
+        match op {
+            SetOp::Union => self.union(other),
+            SetOp::Intersection => self.intersection(other),
+            SetOp::Difference => self.difference(other),
+            SetOp::SymmetricDifference => self.symmetrical_difference(other),
+        }
     }
-    /// Removes all values in another set from this one.
-    #[inline(always)]
-    pub fn remove_all(&mut self, other: Self) {
-        self.__priv_repr = self.__priv_repr.and_not(other.__priv_repr);
+
+    /// Combines this set with `other` by computing the membership of each variant with a closure.
+    ///
+    /// For every variant `v`, the result contains `v` if `f(self.contains(v), other.contains(v))`
+    /// returns `true`. This generalizes operations such as [`EnumSet::union`] and
+    /// [`EnumSet::intersection`].
+    ///
+    /// Unlike the other set operations, this calls `f` once for every variant of `T`, so it runs
+    /// in time proportional to [`EnumSet::variant_count`] rather than the length of the sets.
+    pub fn zip_with<F: FnMut(bool, bool) -> bool>(&self, other: Self, mut f: F) -> Self {
+        let mut remaining = Self::all_bits();
+        let mut result = T::Repr::empty();
+        while !remaining.is_empty() {
+            let bit = remaining.trailing_zeros();
+            remaining.remove_bit(bit);
+            if f(self.__priv_repr.has_bit(bit), other.__priv_repr.has_bit(bit)) {
+                result.add_bit(bit);
+            }
+        }
+        EnumSet { __priv_repr: result }
     }
 
-    /// Iterates the contents of the set in order from the least significant bit to the most
-    /// significant bit.
+    /// Merges this set with a higher priority set that controls only some of the variants.
     ///
-    /// Note that iterator invalidation is impossible as the iterator contains a copy of this type,
-    /// rather than holding a reference to it.
-    pub fn iter(&self) -> EnumSetIter<T> {
-        EnumSetIter::new(*self)
+    /// For the variants in `managed`, the result contains the variant only if `higher` contains
+    /// it. For every other variant, the result contains the variant only if `self` contains it.
+    /// This is equivalent to `(self - managed) | (higher & managed)`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use enumset::*;
+    /// #[derive(EnumSetType, Debug)]
+    /// enum Flag { A, B, C, D }
+    ///
+    /// let base = Flag::A | Flag::B;
+    /// let higher = Flag::C | Flag::D;
+    /// let managed = Flag::B | Flag::C;
+    /// assert_eq!(base.override_with(higher, managed), Flag::A | Flag::C);
+    /// ```
+    #[inline(always)]
+    pub fn override_with(&self, higher: Self, managed: Self) -> Self {
+        self.difference(managed).union(higher.intersection(managed))
     }
 
-    /// Iterates the subsets of the set.
+    /// Computes the changes required to turn this set into `target`.
     ///
-    /// Note that iterator invalidation is impossible as the iterator contains a copy of this type,
-    /// rather than holding a reference to it.
-    pub fn subsets(&self) -> EnumSetSubsetIter<T> {
-        EnumSetSubsetIter::new(*self)
+    /// The variants to add are those in `target` but not in `self`, and the variants to remove
+    /// are those in `self` but not in `target`.
+    #[inline(always)]
+    pub fn diff(&self, target: Self) -> EnumSetDiff<T> {
+        EnumSetDiff { to_add: target - *self, to_remove: *self - target }
     }
 
-    /// Returns a `T::Repr` representing the elements of this set.
+    /// Returns a set with the positions of all elements mirrored.
     ///
-    /// Unlike the other `as_*` methods, this method is zero-cost and guaranteed not to fail,
-    /// panic or truncate any bits.
+    /// An element stored in bit `i` is moved to bit `bit_width() - 1 - i`. This reversal is done
+    /// within [`EnumSet::bit_width`] rather than the full width of the underlying integer, so the
+    /// highest valid variant and the variant with a discriminant of `0` swap places.
     ///
-    /// In order to use this method, the definition of `T` must have the `#[enumset(repr = "…")]`
-    /// annotation.
+    /// For enums with "sparse" variants, elements that would be moved to a position that does not
+    /// correspond to a variant are dropped from the result.
     #[inline(always)]
-    pub fn as_repr(&self) -> <T as EnumSetTypeWithRepr>::Repr
-    where T: EnumSetTypeWithRepr {
-        self.__priv_repr
+    pub fn reverse_bits(&self) -> Self {
+        let reversed = self.__priv_repr.reverse_bits();
+        let shifted = reversed.shift_right(T::Repr::WIDTH - Self::bit_width());
+        EnumSet { __priv_repr: shifted & Self::all_bits() }
     }
 
-    /// Constructs a bitset from a `T::Repr` without checking for invalid bits.
+    /// Returns the elements of this set whose discriminant is a multiple of `step`.
     ///
-    /// Unlike the other `from_*` methods, this method is zero-cost and guaranteed not to fail,
-    /// panic or truncate any bits, provided the conditions under “Safety” are upheld.
+    /// # Panics
     ///
-    /// In order to use this method, the definition of `T` must have the `#[enumset(repr = "…")]`
-    /// annotation.
+    /// This method panics if `step` is `0`.
     ///
-    /// # Safety
+    /// # Example
     ///
-    /// All bits in the provided parameter `bits` that don't correspond to an enum variant of
-    /// `T` must be set to `0`. Behavior is **undefined** if any of these bits are set to `1`.
+    /// ```rust
+    /// # use enumset::*;
+    /// #[derive(EnumSetType, Debug)]
+    /// enum Enum { A, B, C, D }
+    ///
+    /// assert_eq!(EnumSet::<Enum>::all().every_nth_position(2), Enum::A | Enum::C);
+    /// ```
+    pub fn every_nth_position(&self, step: u32) -> Self {
+        assert!(step != 0, "`step` must not be zero.");
+        let mut mask = T::Repr::empty();
+        let mut bit = 0;
+        while bit < Self::bit_width() {
+            mask.add_bit(bit);
+            bit = bit.saturating_add(step);
+        }
+        EnumSet { __priv_repr: self.__priv_repr & mask }
+    }
+
+    /// Returns a set with the positions of all elements rotated towards higher positions by `n`.
+    ///
+    /// The rotation is done within [`EnumSet::bit_width`] rather than the full width of the
+    /// underlying integer, so elements rotated past the highest valid variant wrap around to
+    /// position `0`.
+    ///
+    /// For enums with "sparse" variants, elements that would be moved to a position that does not
+    /// correspond to a variant are dropped from the result.
+    pub fn rotate_left(&self, n: u32) -> Self {
+        let width = Self::bit_width();
+        if width == 0 {
+            return *self;
+        }
+        let n = n % width;
+        let bits = self.__priv_repr;
+        let rotated = bits.shift_left(n) | bits.shift_right(width - n);
+        EnumSet { __priv_repr: rotated & Self::all_bits() }
+    }
+
+    /// Returns a set with the positions of all elements rotated towards lower positions by `n`.
+    ///
+    /// The rotation is done within [`EnumSet::bit_width`] rather than the full width of the
+    /// underlying integer, so elements rotated past position `0` wrap around to the highest valid
+    /// variant.
+    ///
+    /// For enums with "sparse" variants, elements that would be moved to a position that does not
+    /// correspond to a variant are dropped from the result.
+    pub fn rotate_right(&self, n: u32) -> Self {
+        let width = Self::bit_width();
+        if width == 0 {
+            return *self;
+        }
+        self.rotate_left(width - n % width)
+    }
+
+    /// Converts this set into a set of a subset enum, keeping only the elements that are
+    /// variants of `U`.
+    ///
+    /// As the variants of `U` have the same discriminants as the corresponding variants of `T`,
+    /// this is done by discarding every bit that does not correspond to a variant of `U`.
     #[inline(always)]
-    pub unsafe fn from_repr_unchecked(bits: <T as EnumSetTypeWithRepr>::Repr) -> Self
-    where T: EnumSetTypeWithRepr {
-        Self { __priv_repr: bits }
+    pub fn narrow<U: EnumSetSubsetOf<T>>(&self) -> EnumSet<U> {
+        EnumSet::<U>::from_u128_truncated(self.__priv_repr.to_u128())
     }
 
-    /// Constructs a bitset from a `T::Repr`.
+    /// Returns a set with the positions of all elements moved according to a permutation table.
     ///
-    /// If a bit that doesn't correspond to an enum variant is set, this
-    /// method will panic.
+    /// An element stored in bit `i` is moved to bit `perm[i]`. Only the entries of `perm` for
+    /// elements present in the set are used.
     ///
-    /// In order to use this method, the definition of `T` must have the `#[enumset(repr = "…")]`
-    /// annotation.
+    /// This method returns `None` if the permutation cannot be applied to this set. This happens
+    /// if an element's position has no entry in `perm`, if two elements would be moved to the
+    /// same position, or if an element would be moved to a position that does not correspond to
+    /// an enum variant.
+    pub fn apply_permutation(&self, perm: &[u32]) -> Option<Self> {
+        let mut bits = self.__priv_repr;
+        let mut result = T::Repr::empty();
+        while !bits.is_empty() {
+            let bit = bits.trailing_zeros();
+            bits.remove_bit(bit);
+
+            let target = *perm.get(bit as usize)?;
+            if target >= T::Repr::WIDTH || result.has_bit(target) {
+                return None;
+            }
+            result.add_bit(target);
+        }
+        if result.and_not(Self::all_bits()).is_empty() {
+            Some(EnumSet { __priv_repr: result })
+        } else {
+            None
+        }
+    }
+
+    /// Checks whether this set contains a value.
+    ///
+    /// This method, like [`EnumSet::len`] and [`EnumSet::is_empty`], can be used in `const`
+    /// contexts:
+    ///
+    /// ```rust
+    /// # use enumset::*;
+    /// #[derive(EnumSetType, Debug)]
+    /// enum Enum { A, B, C }
+    ///
+    /// const SET: EnumSet<Enum> = enum_set!(Enum::A | Enum::C);
+    /// const _: () = assert!(SET.contains(Enum::A) && !SET.contains(Enum::B));
+    /// ```
     #[inline(always)]
-    pub fn from_repr(bits: <T as EnumSetTypeWithRepr>::Repr) -> Self
-    where T: EnumSetTypeWithRepr {
-        Self::try_from_repr(bits).expect("Bitset contains invalid variants.")
+    pub const fn contains(&self, value: T) -> bool {
+        let bit = repr::const_enum_into_u32(value);
+        repr::const_repr_to_u128(self.__priv_repr) & (1 << bit) != 0
     }
 
-    /// Attempts to constructs a bitset from a `T::Repr`.
+    /// Checks whether the bit at a given position is set.
     ///
-    /// If a bit that doesn't correspond to an enum variant is set, this
-    /// method will return `None`.
+    /// Positions that do not correspond to a variant, including positions beyond the width of
+    /// the underlying integer, return `false` instead of panicking.
+    #[inline(always)]
+    pub fn bit(&self, position: u32) -> bool {
+        position < T::Repr::WIDTH && self.__priv_repr.has_bit(position)
+    }
+
+    /// Returns the variant with the lowest discriminant present in this set.
     ///
-    /// In order to use this method, the definition of `T` must have the `#[enumset(repr = "…")]`
-    /// annotation.
+    /// Returns `None` if the set is empty.
     #[inline(always)]
-    pub fn try_from_repr(bits: <T as EnumSetTypeWithRepr>::Repr) -> Option<Self>
-    where T: EnumSetTypeWithRepr {
-        let mask = Self::all().__priv_repr;
-        if bits.and_not(mask).is_empty() {
-            Some(EnumSet { __priv_repr: bits })
+    pub fn first(&self) -> Option<T> {
+        if self.is_empty() {
+            None
         } else {
+            unsafe { Some(T::enum_from_u32(self.__priv_repr.trailing_zeros())) }
+        }
+    }
+    /// Returns the variant with the highest discriminant present in this set.
+    ///
+    /// Returns `None` if the set is empty.
+    #[inline(always)]
+    pub fn last(&self) -> Option<T> {
+        if self.is_empty() {
             None
+        } else {
+            let bit = T::Repr::WIDTH - 1 - self.__priv_repr.leading_zeros();
+            unsafe { Some(T::enum_from_u32(bit)) }
         }
     }
 
-    /// Constructs a bitset from a `T::Repr`, ignoring invalid variants.
+    /// Returns the variant with the lowest discriminant that is not present in this set.
     ///
-    /// In order to use this method, the definition of `T` must have the `#[enumset(repr = "…")]`
-    /// annotation.
+    /// Only valid variants are considered, so for enums with "sparse" variants, positions that do
+    /// not correspond to a variant are skipped. Returns `None` if the set contains every variant.
     #[inline(always)]
-    pub fn from_repr_truncated(bits: <T as EnumSetTypeWithRepr>::Repr) -> Self
-    where T: EnumSetTypeWithRepr {
-        let mask = Self::all().as_repr();
-        let bits = bits & mask;
-        EnumSet { __priv_repr: bits }
+    pub fn first_absent(&self) -> Option<T> {
+        let absent = self.complement().__priv_repr;
+        if absent.is_empty() {
+            None
+        } else {
+            unsafe { Some(T::enum_from_u32(absent.trailing_zeros())) }
+        }
+    }
+    /// Returns the variant with the highest discriminant that is not present in this set.
+    ///
+    /// Only valid variants are considered, so for enums with "sparse" variants, positions that do
+    /// not correspond to a variant are skipped. Returns `None` if the set contains every variant.
+    #[inline(always)]
+    pub fn last_absent(&self) -> Option<T> {
+        let absent = self.complement().__priv_repr;
+        if absent.is_empty() {
+            None
+        } else {
+            unsafe { Some(T::enum_from_u32(T::Repr::WIDTH - 1 - absent.leading_zeros())) }
+        }
     }
-}
 
-/// Helper macro for generating conversion functions.
-macro_rules! conversion_impls {
-    (
-        $(for_num!(
-            $underlying:ty, $underlying_str:expr,
-            $from_fn:ident $to_fn:ident $from_fn_opt:ident $to_fn_opt:ident,
-            $from:ident $try_from:ident $from_truncated:ident $from_unchecked:ident,
-            $to:ident $try_to:ident $to_truncated:ident
-        );)*
-    ) => {
-        impl <T : EnumSetType> EnumSet<T> {$(
-            #[doc = "Returns a `"]
-            #[doc = $underlying_str]
-            #[doc = "` representing the elements of this set.\n\nIf the underlying bitset will \
-                     not fit in a `"]
-            #[doc = $underlying_str]
-            #[doc = "` or contains bits that do not correspond to an enum variant, this method \
-                     will panic."]
-            #[inline(always)]
-            pub fn $to(&self) -> $underlying {
-                self.$try_to().expect("Bitset will not fit into this type.")
-            }
+    /// Converts a set containing exactly one element into that element.
+    ///
+    /// If the set does not contain exactly one element, the set is returned as an error instead.
+    #[inline(always)]
+    pub fn into_single(self) -> Result<T, Self> {
+        if self.len() == 1 {
+            unsafe { Ok(T::enum_from_u32(self.__priv_repr.trailing_zeros())) }
+        } else {
+            Err(self)
+        }
+    }
 
-            #[doc = "Tries to return a `"]
-            #[doc = $underlying_str]
+    /// Returns the element at a given index among the elements of this set, in order from the
+    /// least significant bit to the most significant bit.
+    ///
+    /// For example, in the set `A | C | D`, the element at index `1` is `C`. If the index is not
+    /// less than the length of the set, `None` is returned.
+    ///
+    /// This is the inverse of [`EnumSet::position_of`].
+    #[inline(always)]
+    pub fn nth(&self, index: usize) -> Option<T> {
+        if index >= self.len() {
+            return None;
+        }
+        let mut bits = self.__priv_repr;
+        for _ in 0..index {
+            bits.remove_bit(bits.trailing_zeros());
+        }
+        unsafe { Some(T::enum_from_u32(bits.trailing_zeros())) }
+    }
+
+    /// Returns the index of a value among the elements of this set, in order from the least
+    /// significant bit to the most significant bit.
+    ///
+    /// For example, in the set `A | C | D`, the position of `C` is `Some(1)`. If the value is not
+    /// present in the set, `None` is returned.
+    ///
+    /// This is also known as the rank of the value within the set, and is the inverse of
+    /// [`EnumSet::nth`]. Together, they map the elements of the set to `0..self.len()` while
+    /// preserving their order.
+    #[inline(always)]
+    pub fn position_of(&self, value: T) -> Option<usize> {
+        if self.contains(value) {
+            let bit = value.enum_into_u32();
+            Some(self.len() - self.__priv_repr.count_remaining_ones(bit))
+        } else {
+            None
+        }
+    }
+
+    /// Adds a value to this set.
+    ///
+    /// If the set did not have this value present, `true` is returned.
+    ///
+    /// If the set did have this value present, `false` is returned.
+    #[inline(always)]
+    pub fn insert(&mut self, value: T) -> bool {
+        let contains = !self.contains(value);
+        self.__priv_repr.add_bit(value.enum_into_u32());
+        contains
+    }
+    /// Removes a value from this set. Returns whether the value was present in the set.
+    #[inline(always)]
+    pub fn remove(&mut self, value: T) -> bool {
+        let contains = self.contains(value);
+        self.__priv_repr.remove_bit(value.enum_into_u32());
+        contains
+    }
+    /// Removes a value from this set, returning an error if the value was not present.
+    ///
+    /// If an error is returned, the set is unchanged.
+    #[inline(always)]
+    pub fn try_remove(&mut self, value: T) -> Result<(), T> {
+        if self.remove(value) {
+            Ok(())
+        } else {
+            Err(value)
+        }
+    }
+
+    /// Toggles the presence of a value in this set. Returns whether the value is now present in
+    /// the set.
+    #[inline(always)]
+    pub fn toggle(&mut self, value: T) -> bool {
+        self.__priv_repr = self.__priv_repr ^ EnumSet::only(value).__priv_repr;
+        self.contains(value)
+    }
+
+    /// Removes the variant with the lowest discriminant from this set, and returns it.
+    ///
+    /// Returns `None` and leaves the set unchanged if it is empty.
+    #[inline(always)]
+    pub fn pop_first(&mut self) -> Option<T> {
+        let value = self.first()?;
+        self.remove(value);
+        Some(value)
+    }
+    /// Removes the variant with the highest discriminant from this set, and returns it.
+    ///
+    /// Returns `None` and leaves the set unchanged if it is empty.
+    #[inline(always)]
+    pub fn pop_last(&mut self) -> Option<T> {
+        let value = self.last()?;
+        self.remove(value);
+        Some(value)
+    }
+
+    /// Temporarily adds a value to this set, returning a guard that removes it again when dropped.
+    ///
+    /// If the value was already present in the set, dropping the guard leaves the set unchanged.
+    /// The set can be accessed through the guard while it is alive.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use enumset::*;
+    /// #[derive(EnumSetType, Debug)]
+    /// enum Flag { A, B, C }
+    ///
+    /// let mut set = EnumSet::only(Flag::A);
+    /// {
+    ///     let guard = set.scoped_insert(Flag::B);
+    ///     assert_eq!(*guard, Flag::A | Flag::B);
+    /// }
+    /// assert_eq!(set, Flag::A);
+    /// ```
+    #[inline(always)]
+    pub fn scoped_insert(&mut self, value: T) -> InsertGuard<'_, T> {
+        let newly_inserted = self.insert(value);
+        InsertGuard { set: self, value, newly_inserted }
+    }
+
+    /// Adds all elements in another set to this one.
+    #[inline(always)]
+    pub fn insert_all(&mut self, other: Self) {
+        self.__priv_repr = self.__priv_repr | other.__priv_repr
+    }
+
+    /// Removes all elements from this set that are not in another set. Returns whether this set
+    /// was changed.
+    ///
+    /// This is useful for detecting when repeated intersections have reached a fixpoint.
+    #[inline(always)]
+    pub fn intersect_with_changed(&mut self, other: Self) -> bool {
+        let old = *self;
+        *self &= other;
+        *self != old
+    }
+
+    /// Adds all elements in another set to this one, if the two sets have no elements in common.
+    ///
+    /// If the sets overlap, this set is left unchanged and the elements present in both sets are
+    /// returned as an error.
+    #[inline(always)]
+    pub fn try_insert_all(&mut self, other: Self) -> Result<(), Self> {
+        let overlap = *self & other;
+        if overlap.is_empty() {
+            self.insert_all(other);
+            Ok(())
+        } else {
+            Err(overlap)
+        }
+    }
+    /// Removes all values in another set from this one.
+    #[inline(always)]
+    pub fn remove_all(&mut self, other: Self) {
+        self.__priv_repr = self.__priv_repr.and_not(other.__priv_repr);
+    }
+    /// Toggles the presence of all values in another set in this one.
+    ///
+    /// Values in the other set that are not in this set are added, and values that are in both
+    /// sets are removed.
+    #[inline(always)]
+    pub fn toggle_all(&mut self, other: Self) {
+        self.__priv_repr = self.__priv_repr ^ other.__priv_repr;
+    }
+
+    /// Adds all values in a slice to this set.
+    ///
+    /// This is equivalent to calling [`EnumSet::insert`] on every value in the slice, but does
+    /// not check whether each value was already present.
+    #[inline(always)]
+    pub fn extend_from_slice(&mut self, slice: &[T]) {
+        let mut bits = self.__priv_repr;
+        for value in slice {
+            bits.add_bit(value.enum_into_u32());
+        }
+        self.__priv_repr = bits;
+    }
+
+    /// Toggles the presence of each value produced by an iterator.
+    ///
+    /// Values not in the set are added, and values already in the set are removed. As each value
+    /// is toggled separately, a value produced an even number of times leaves the set unchanged,
+    /// while a value produced an odd number of times is toggled once. This differs from
+    /// [`Extend::extend`], which only ever adds values.
+    pub fn toggle_each<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let mut bits = self.__priv_repr;
+        for value in iter {
+            let bit = value.enum_into_u32();
+            if bits.has_bit(bit) {
+                bits.remove_bit(bit);
+            } else {
+                bits.add_bit(bit);
+            }
+        }
+        self.__priv_repr = bits;
+    }
+
+    /// Iterates the contents of the set in order from the least significant bit to the most
+    /// significant bit.
+    ///
+    /// Note that iterator invalidation is impossible as the iterator contains a copy of this type,
+    /// rather than holding a reference to it.
+    pub fn iter(&self) -> EnumSetIter<T> {
+        EnumSetIter::new(*self)
+    }
+
+    /// Returns an adapter that displays the elements of this set separated by `sep`.
+    ///
+    /// The [`Display`] implementation of `EnumSet` uses `" | "` as the separator.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use enumset::*;
+    /// # use std::fmt;
+    /// #[derive(EnumSetType, Debug)]
+    /// enum Enum { A, B, C }
+    /// impl fmt::Display for Enum {
+    ///     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    ///         fmt::Debug::fmt(self, f)
+    ///     }
+    /// }
+    ///
+    /// assert_eq!((Enum::A | Enum::C).display_with(", ").to_string(), "A, C");
+    /// ```
+    pub fn display_with<'a>(&self, sep: &'a str) -> EnumSetDisplay<'a, T> {
+        EnumSetDisplay { set: *self, sep }
+    }
+
+    /// Iterates the contents of the set, pairing each element with the entry of `values` at the
+    /// index of its discriminant.
+    ///
+    /// Elements whose discriminant is not a valid index into `values` are skipped. For enums with
+    /// "sparse" variants (e.g. `enum Foo { A = 10, B = 20 }`), `values` must be longer than the
+    /// highest discriminant of interest, not just the number of variants.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use enumset::*;
+    /// #[derive(EnumSetType, Debug)]
+    /// enum Enum { A, B, C, D }
+    ///
+    /// let values = ["a", "b", "c"];
+    /// let set = Enum::A | Enum::C | Enum::D;
+    /// let pairs: Vec<_> = set.zip_values(&values).collect();
+    /// assert_eq!(pairs, vec![(Enum::A, &"a"), (Enum::C, &"c")]);
+    /// ```
+    pub fn zip_values<'a, V>(&self, values: &'a [V]) -> EnumSetZipValuesIter<'a, T, V> {
+        EnumSetZipValuesIter::new(*self, values)
+    }
+
+    /// Removes the elements of the set one at a time, returning them in order from the least
+    /// significant bit to the most significant bit.
+    ///
+    /// Each element is removed from the set as it is yielded, so the set is empty once the
+    /// iterator is exhausted. If the iterator is dropped early, the elements that were not yet
+    /// yielded remain in the set.
+    pub fn drain(&mut self) -> EnumSetDrain<'_, T> {
+        EnumSetDrain::new(self)
+    }
+
+    /// Iterates the bit positions of the elements of the set, in order from the least significant
+    /// bit to the most significant bit.
+    ///
+    /// This yields the same values as `self.iter().map(|v| v as u32)`, but without converting
+    /// each position back into a variant. This is useful for indexing arrays that run parallel to
+    /// the discriminants of the enum.
+    pub fn iter_indices(&self) -> BitPositionsIter {
+        BitPositionsIter::new(self.__priv_repr.to_u128())
+    }
+
+    /// Iterates the contents of the set along with the bit position of each element, in order
+    /// from the least significant bit to the most significant bit.
+    ///
+    /// This is mainly useful for debugging, to correlate enum variants with their positions in
+    /// the underlying bitset.
+    pub fn debug_positions(&self) -> EnumSetPositionsIter<T> {
+        EnumSetPositionsIter::new(*self)
+    }
+
+    /// Applies a function to each element of the set in order from the least significant bit to
+    /// the most significant bit, and returns the first non-`None` result.
+    ///
+    /// No further elements are visited once `f` returns `Some`.
+    pub fn find_map_variant<R, F: FnMut(T) -> Option<R>>(&self, f: F) -> Option<R> {
+        self.iter().find_map(f)
+    }
+
+    /// Returns the element of the set for which `f` returns the largest key, or `None` if the set
+    /// is empty.
+    ///
+    /// If several elements have the largest key, the one with the lowest discriminant is returned.
+    pub fn max_by_key<K: Ord, F: FnMut(T) -> K>(&self, mut f: F) -> Option<T> {
+        // `Iterator::max_by_key` returns the last of several equal maximums, so iterate in
+        // reverse order to return the one with the lowest discriminant.
+        self.iter().rev().max_by_key(|&v| f(v))
+    }
+
+    /// Returns the element of the set for which `f` returns the smallest key, or `None` if the
+    /// set is empty.
+    ///
+    /// If several elements have the smallest key, the one with the lowest discriminant is
+    /// returned.
+    pub fn min_by_key<K: Ord, F: FnMut(T) -> K>(&self, mut f: F) -> Option<T> {
+        self.iter().min_by_key(|&v| f(v))
+    }
+
+    /// Returns the set of values implied by the elements of this set, directly or transitively.
+    ///
+    /// Starting from this set, every value in `implies(v)` is added for each element `v`, including
+    /// elements that were themselves added due to an implication. This repeats until a fixpoint
+    /// is reached, where no further values are implied. The result always contains this set.
+    ///
+    /// `implies` is called at most once for each variant, so this always terminates after at most
+    /// [`EnumSet::variant_count`] calls, even if the implications are cyclic.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use enumset::*;
+    /// #[derive(EnumSetType, Debug)]
+    /// enum Feature { Gui, Render, Window, Audio }
+    ///
+    /// let implies = |f| match f {
+    ///     Feature::Gui => Feature::Render | Feature::Window,
+    ///     Feature::Render => EnumSet::only(Feature::Window),
+    ///     _ => EnumSet::empty(),
+    /// };
+    /// let enabled = EnumSet::only(Feature::Gui).saturate(implies);
+    /// assert_eq!(enabled, Feature::Gui | Feature::Render | Feature::Window);
+    /// ```
+    pub fn saturate<F: Fn(T) -> EnumSet<T>>(&self, implies: F) -> Self {
+        let mut result = *self;
+        let mut pending = *self;
+        while !pending.is_empty() {
+            let bit = pending.__priv_repr.trailing_zeros();
+            pending.__priv_repr.remove_bit(bit);
+
+            // Every value is added to `pending` at most once, as it is only added when it is not
+            // already in `result`.
+            let added = implies(unsafe { T::enum_from_u32(bit) }) - result;
+            result |= added;
+            pending |= added;
+        }
+        result
+    }
+
+    /// Removes every element of the set that is implied by the other elements, returning a minimal
+    /// set with the same [`saturated`](EnumSet::saturate) result.
+    ///
+    /// This can be used to find the values that were explicitly chosen, as opposed to the ones
+    /// that were only added because they were implied.
+    ///
+    /// The minimal set may not be unique, e.g. if two values imply each other. In that case,
+    /// elements are considered for removal starting from the highest discriminant, so elements
+    /// with lower discriminants are preferentially kept.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use enumset::*;
+    /// #[derive(EnumSetType, Debug)]
+    /// enum Feature { Gui, Render, Window, Audio }
+    ///
+    /// let implies = |f| match f {
+    ///     Feature::Gui => Feature::Render | Feature::Window,
+    ///     Feature::Render => EnumSet::only(Feature::Window),
+    ///     _ => EnumSet::empty(),
+    /// };
+    /// let enabled = Feature::Gui | Feature::Render | Feature::Window | Feature::Audio;
+    /// assert_eq!(enabled.minimize(implies), Feature::Gui | Feature::Audio);
+    /// ```
+    pub fn minimize<F: Fn(T) -> EnumSet<T>>(&self, implies: F) -> Self {
+        let mut result = *self;
+        for v in self.iter().rev() {
+            let rest = result - v;
+            if rest.saturate(&implies).contains(v) {
+                result = rest;
+            }
+        }
+        result
+    }
+
+    /// Returns a set containing only the elements of this set for which a predicate returns
+    /// `true`.
+    ///
+    /// `f` is called once for each element of this set, and this set is left unchanged.
+    pub fn filter<F: FnMut(T) -> bool>(&self, mut f: F) -> Self {
+        let mut bits = self.__priv_repr;
+        let mut remaining = self.__priv_repr;
+        while !remaining.is_empty() {
+            let bit = remaining.trailing_zeros();
+            remaining.remove_bit(bit);
+            if !f(unsafe { T::enum_from_u32(bit) }) {
+                bits.remove_bit(bit);
+            }
+        }
+        EnumSet { __priv_repr: bits }
+    }
+
+    /// Returns the number of elements of this set for which a predicate returns `true`.
+    ///
+    /// `f` is called once for each element of this set. This is equivalent to
+    /// `self.filter(f).len()`, without building the intermediate set.
+    pub fn count_matching<F: FnMut(T) -> bool>(&self, mut f: F) -> usize {
+        self.iter().filter(|&v| f(v)).count()
+    }
+
+    /// Splits this set into two sets according to a predicate.
+    ///
+    /// The first set returned contains the elements for which `f` returns `true`, and the second
+    /// contains the elements for which it returns `false`. `f` is called once for each element of
+    /// this set.
+    pub fn partition_by<F: FnMut(T) -> bool>(&self, f: F) -> (Self, Self) {
+        let matching = self.filter(f);
+        (matching, *self - matching)
+    }
+
+    /// Returns a set of another enum type, containing the result of applying a function to each
+    /// element of this set.
+    ///
+    /// As multiple elements may be mapped to the same value, the returned set may contain fewer
+    /// elements than this one.
+    pub fn map<U: EnumSetType, F: FnMut(T) -> U>(&self, mut f: F) -> EnumSet<U> {
+        let mut result = EnumSet::new();
+        for v in self.iter() {
+            result.insert(f(v));
+        }
+        result
+    }
+
+    /// Returns a set of another enum type, containing the result of applying a fallible function
+    /// to each element of this set.
+    ///
+    /// The elements are visited in order from the least significant bit to the most significant
+    /// bit, and the first error returned by `f` is returned immediately without visiting the
+    /// remaining elements.
+    pub fn try_map<U, E, F>(&self, mut f: F) -> Result<EnumSet<U>, E>
+    where
+        U: EnumSetType,
+        F: FnMut(T) -> Result<U, E>,
+    {
+        let mut result = EnumSet::new();
+        for v in self.iter() {
+            result.insert(f(v)?);
+        }
+        Ok(result)
+    }
+
+    /// Returns the first element of the set for which `pred` returns `false`, or `None` if it
+    /// returns `true` for every element.
+    ///
+    /// The set is assumed to be partitioned according to `pred`, meaning that `pred` returns
+    /// `true` for every element with a lower discriminant than any element it returns `false` for.
+    /// If this is not the case, the returned result is unspecified and meaningless, as with
+    /// [`slice::partition_point`].
+    ///
+    /// This performs a binary search over the elements in the set, calling `pred` a number of
+    /// times logarithmic in the length of the set.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use enumset::*;
+    /// #[derive(EnumSetType, Debug)]
+    /// enum Level { Trace, Debug, Info, Warn, Error }
+    ///
+    /// let enabled = Level::Debug | Level::Warn | Level::Error;
+    /// let minimum = Level::Info as u32;
+    /// assert_eq!(enabled.partition_point(|l| (l as u32) < minimum), Some(Level::Warn));
+    /// ```
+    pub fn partition_point<F: FnMut(T) -> bool>(&self, mut pred: F) -> Option<T> {
+        let nth = |mut n: usize| {
+            let mut bits = self.__priv_repr;
+            while n != 0 {
+                bits.remove_bit(bits.trailing_zeros());
+                n -= 1;
+            }
+            unsafe { T::enum_from_u32(bits.trailing_zeros()) }
+        };
+
+        let (mut lo, mut hi) = (0, self.len());
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            if pred(nth(mid)) {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+        if lo < self.len() {
+            Some(nth(lo))
+        } else {
+            None
+        }
+    }
+
+    /// Iterates the maximal runs of elements with consecutive discriminants in this set, as
+    /// `(first, last)` pairs of the elements in each run.
+    ///
+    /// For example, the set `A | B | C | E` has the runs `(A, C)` and `(E, E)`. Runs are yielded
+    /// in order from the least significant bit to the most significant bit.
+    ///
+    /// Note that runs are based on consecutive discriminants, rather than consecutive variants.
+    /// For enums with "sparse" variants (e.g. `enum Foo { A = 10, B = 20 }`), variants with a gap
+    /// between their discriminants are never part of the same run.
+    pub fn runs(&self) -> EnumSetRunsIter<T> {
+        EnumSetRunsIter::new(*self)
+    }
+
+    /// Iterates the subsets of the set.
+    ///
+    /// Note that iterator invalidation is impossible as the iterator contains a copy of this type,
+    /// rather than holding a reference to it.
+    pub fn subsets(&self) -> EnumSetSubsetIter<T> {
+        EnumSetSubsetIter::new(*self)
+    }
+
+    /// Iterates the subsets of the set, each paired with the elements of the set not in it.
+    ///
+    /// The subsets are yielded in the same order as [`EnumSet::subsets`]. For each pair, the two
+    /// sets are disjoint and their union is this set.
+    ///
+    /// Note that iterator invalidation is impossible as the iterator contains a copy of this type,
+    /// rather than holding a reference to it.
+    pub fn subsets_with_complement(&self) -> EnumSetSubsetComplementIter<T> {
+        EnumSetSubsetComplementIter::new(*self)
+    }
+
+    /// Iterates the subsets of the set with exactly `k` elements.
+    ///
+    /// If `k` is `0`, only the empty set is yielded. If `k` is greater than the length of the set,
+    /// nothing is yielded.
+    ///
+    /// Note that iterator invalidation is impossible as the iterator contains a copy of this type,
+    /// rather than holding a reference to it.
+    pub fn subsets_of_size(&self, k: usize) -> EnumSetSizedSubsetIter<T> {
+        EnumSetSizedSubsetIter::new(*self, k)
+    }
+
+    /// Folds every subset of the set into an accumulator, starting from the empty set and ending
+    /// with the set itself.
+    ///
+    /// This visits exactly `2^len` subsets, in the same order as [`EnumSet::subsets`]. This is
+    /// useful for dynamic programming over subsets. Note that for large sets, the number of
+    /// subsets visited grows very quickly.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use enumset::*;
+    /// #[derive(EnumSetType, Debug)]
+    /// enum Enum { A, B, C }
+    ///
+    /// let total_len = EnumSet::<Enum>::all().fold_subsets(0, |acc, subset| acc + subset.len());
+    /// assert_eq!(total_len, 12);
+    /// ```
+    pub fn fold_subsets<B, F: FnMut(B, EnumSet<T>) -> B>(&self, init: B, f: F) -> B {
+        self.subsets().fold(init, f)
+    }
+
+    /// Iterates every sequence of `count` variants of the enum, with repetition allowed.
+    ///
+    /// The sequences are yielded in lexicographic order by discriminant, treating each sequence
+    /// as a `count` digit number in base [`EnumSet::variant_count`]. There are
+    /// `variant_count().pow(count)` such sequences, so the length of this iterator grows
+    /// exponentially with `count`.
+    ///
+    /// This method requires the `alloc` feature.
+    #[cfg(feature = "alloc")]
+    pub fn tuples_of(count: usize) -> EnumSetTuplesIter<T> {
+        EnumSetTuplesIter::new(count)
+    }
+
+    /// Returns an iterator over the elements of this set in a random order.
+    ///
+    /// The iterator yields exactly [`EnumSet::len`] items, and every element of the set is
+    /// yielded exactly once. The order is chosen uniformly at random with a Fisher–Yates shuffle,
+    /// which is performed without allocating.
+    ///
+    /// This method is only available with the `rand` feature.
+    #[cfg(feature = "rand")]
+    pub fn shuffled<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> EnumSetShuffledIter<T> {
+        EnumSetShuffledIter::new(*self, rng)
+    }
+
+    /// Randomly chooses an element of this set, with every element equally likely to be chosen.
+    ///
+    /// If the set is empty, this method returns `None`.
+    ///
+    /// This method is only available with the `rand` feature.
+    #[cfg(feature = "rand")]
+    pub fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> Option<T> {
+        if self.is_empty() {
+            None
+        } else {
+            self.iter().nth(rng.gen_range(0..self.len()))
+        }
+    }
+
+    /// Randomly chooses an element of this set, with a probability proportional to its weight.
+    ///
+    /// `weights` is called once for each element of the set. Weights that are zero, negative,
+    /// infinite or NaN are treated as zero, so the corresponding elements are never chosen. If the
+    /// set is empty or no element has a positive finite weight, this method returns `None`.
+    ///
+    /// This method is only available with the `rand` feature.
+    #[cfg(feature = "rand")]
+    pub fn weighted_choice<R, W, F>(&self, rng: &mut R, mut weights: F) -> Option<T>
+    where
+        R: rand::Rng + ?Sized,
+        W: Into<f64>,
+        F: FnMut(T) -> W,
+    {
+        let mut element_weights = [0.0; 128];
+        let mut max = 0.0;
+        for v in self.iter() {
+            let weight = weights(v).into();
+            if weight.is_finite() && weight > 0.0 {
+                element_weights[v.enum_into_u32() as usize] = weight;
+                if weight > max {
+                    max = weight;
+                }
+            }
+        }
+        if max == 0.0 {
+            return None;
+        }
+
+        // Scale the weights by the largest one, so their sum cannot overflow to infinity.
+        let mut total = 0.0;
+        for weight in element_weights.iter_mut() {
+            *weight /= max;
+            total += *weight;
+        }
+
+        let target = rng.gen::<f64>() * total;
+        let mut accum = 0.0;
+        let mut chosen = None;
+        for v in self.iter() {
+            let weight = element_weights[v.enum_into_u32() as usize];
+            if weight > 0.0 {
+                accum += weight;
+                chosen = Some(v);
+                if target < accum {
+                    break;
+                }
+            }
+        }
+        // If rounding errors cause `target` to never be below `accum`, this is the last element
+        // with a positive weight.
+        chosen
+    }
+
+    /// Collects the elements of this set into a `SmallVec`, in ascending order.
+    ///
+    /// The returned vector has an inline capacity of 16 elements, so it does not allocate for
+    /// sets with up to 16 elements. In particular, sets of enums with 16 or fewer variants never
+    /// allocate.
+    ///
+    /// This method is only available with the `smallvec` feature.
+    #[cfg(feature = "smallvec")]
+    pub fn to_smallvec(&self) -> smallvec::SmallVec<[T; 16]> {
+        self.iter().collect()
+    }
+
+    /// Returns a `Vec` of the elements of this set, sorted by a key.
+    ///
+    /// Note that [`EnumSet::iter`] already yields elements in order of their discriminants. This
+    /// method is useful when another order is needed, such as sorting by display name. The sort is
+    /// stable, so elements with equal keys remain ordered by discriminant.
+    ///
+    /// This method requires the `alloc` feature.
+    #[cfg(feature = "alloc")]
+    pub fn to_sorted_vec_by_key<K: Ord, F: FnMut(&T) -> K>(&self, f: F) -> Vec<T> {
+        let mut vec: Vec<T> = self.iter().collect();
+        vec.sort_by_key(f);
+        vec
+    }
+
+    /// Returns a `Vec` pairing each element of this set with a value computed from it.
+    ///
+    /// The elements are visited in order from the least significant bit to the most significant
+    /// bit.
+    ///
+    /// This method requires the `alloc` feature.
+    #[cfg(feature = "alloc")]
+    pub fn map_to_values<V, F: FnMut(T) -> V>(&self, mut f: F) -> Vec<(T, V)> {
+        self.iter().map(|v| (v, f(v))).collect()
+    }
+
+    /// Returns the name of every variant of `T` paired with whether it is present in this set,
+    /// in order of ascending discriminant.
+    ///
+    /// Unlike iterating the set, this includes variants that are absent from the set. This can
+    /// be used to produce exhaustive listings of flags, such as `{"A": true, "B": false}`.
+    ///
+    /// This method is only available with the `alloc` feature.
+    #[cfg(feature = "alloc")]
+    pub fn to_state_pairs(&self) -> Vec<(&'static str, bool)> {
+        Self::all()
+            .iter()
+            .map(|v| (v.enum_name(), self.contains(v)))
+            .collect()
+    }
+
+    /// Returns `true` if no bits that don't correspond to an enum variant are set.
+    ///
+    /// This is always the case for sets constructed without `unsafe` code. It can be used to
+    /// check sets constructed with methods such as [`EnumSet::from_repr_unchecked`], or received
+    /// through FFI.
+    #[inline(always)]
+    pub fn is_valid(&self) -> bool {
+        self.__priv_repr.and_not(Self::all_bits()).is_empty()
+    }
+
+    /// Returns a structured description of the bits of this set, for use in debugging tools.
+    ///
+    /// The report includes any bits that don't correspond to an enum variant, which can only be
+    /// set by misusing `unsafe` constructors such as [`EnumSet::from_repr_unchecked`].
+    pub fn bit_report(&self) -> BitReport {
+        let bits = self.__priv_repr.to_u128();
+        let valid = Self::all_bits().to_u128();
+        BitReport::new(T::Repr::WIDTH, bits & valid, valid & !bits, bits & !valid)
+    }
+
+    /// Panics if any bits that don't correspond to an enum variant are set.
+    ///
+    /// This check is only performed in builds with debug assertions enabled. See
+    /// [`EnumSet::is_valid`].
+    #[inline(always)]
+    #[track_caller]
+    pub fn debug_assert_valid(&self) {
+        debug_assert!(
+            self.is_valid(),
+            "EnumSet contains bits that do not correspond to a variant."
+        );
+    }
+
+    /// Returns a `T::Repr` representing the elements of this set.
+    ///
+    /// Unlike the other `as_*` methods, this method is zero-cost and guaranteed not to fail,
+    /// panic or truncate any bits.
+    ///
+    /// In order to use this method, the definition of `T` must have the `#[enumset(repr = "…")]`
+    /// annotation. The same conversion is also available through [`From`]:
+    ///
+    /// ```rust
+    /// # use enumset::*;
+    /// #[derive(EnumSetType, Debug)]
+    /// #[enumset(repr = "u32")]
+    /// enum Flag { Read, Write, Exec }
+    ///
+    /// extern "C" fn set_flags(flags: u32) -> u32 {
+    ///     flags
+    /// }
+    ///
+    /// let set = Flag::Read | Flag::Exec;
+    /// let bits: u32 = set.into();
+    /// assert_eq!(bits, 0b101);
+    /// assert_eq!(set_flags(set.into()), set.as_repr());
+    /// ```
+    #[inline(always)]
+    pub fn as_repr(&self) -> <T as EnumSetTypeWithRepr>::Repr
+    where T: EnumSetTypeWithRepr {
+        self.__priv_repr
+    }
+
+    /// Returns the size of `T::Repr` in bytes.
+    ///
+    /// `EnumSet<T>` is guaranteed to have the same size as `T::Repr`, so this is also the size of
+    /// `EnumSet<T>`.
+    ///
+    /// In order to use this method, the definition of `T` must have the `#[enumset(repr = "…")]`
+    /// annotation.
+    #[inline(always)]
+    pub const fn repr_size() -> usize
+    where T: EnumSetTypeWithRepr {
+        core::mem::size_of::<<T as EnumSetTypeWithRepr>::Repr>()
+    }
+
+    /// Returns the alignment of `T::Repr` in bytes.
+    ///
+    /// `EnumSet<T>` is guaranteed to have the same alignment as `T::Repr`, so this is also the
+    /// alignment of `EnumSet<T>`.
+    ///
+    /// In order to use this method, the definition of `T` must have the `#[enumset(repr = "…")]`
+    /// annotation.
+    #[inline(always)]
+    pub const fn repr_align() -> usize
+    where T: EnumSetTypeWithRepr {
+        core::mem::align_of::<<T as EnumSetTypeWithRepr>::Repr>()
+    }
+
+    /// Constructs a bitset from a `T::Repr` without checking for invalid bits.
+    ///
+    /// Unlike the other `from_*` methods, this method is zero-cost and guaranteed not to fail,
+    /// panic or truncate any bits, provided the conditions under “Safety” are upheld.
+    ///
+    /// In order to use this method, the definition of `T` must have the `#[enumset(repr = "…")]`
+    /// annotation.
+    ///
+    /// # Safety
+    ///
+    /// All bits in the provided parameter `bits` that don't correspond to an enum variant of
+    /// `T` must be set to `0`. Behavior is **undefined** if any of these bits are set to `1`.
+    #[inline(always)]
+    pub unsafe fn from_repr_unchecked(bits: <T as EnumSetTypeWithRepr>::Repr) -> Self
+    where T: EnumSetTypeWithRepr {
+        Self { __priv_repr: bits }
+    }
+
+    /// Constructs a bitset from a `T::Repr`.
+    ///
+    /// If a bit that doesn't correspond to an enum variant is set, this
+    /// method will panic.
+    ///
+    /// In order to use this method, the definition of `T` must have the `#[enumset(repr = "…")]`
+    /// annotation.
+    #[inline(always)]
+    pub fn from_repr(bits: <T as EnumSetTypeWithRepr>::Repr) -> Self
+    where T: EnumSetTypeWithRepr {
+        Self::try_from_repr(bits).expect("Bitset contains invalid variants.")
+    }
+
+    /// Attempts to constructs a bitset from a `T::Repr`.
+    ///
+    /// If a bit that doesn't correspond to an enum variant is set, this
+    /// method will return `None`. The same conversion is also available through [`TryFrom`],
+    /// which reports the invalid bits in its error instead.
+    ///
+    /// In order to use this method, the definition of `T` must have the `#[enumset(repr = "…")]`
+    /// annotation.
+    #[inline(always)]
+    pub fn try_from_repr(bits: <T as EnumSetTypeWithRepr>::Repr) -> Option<Self>
+    where T: EnumSetTypeWithRepr {
+        let mask = Self::all().__priv_repr;
+        if bits.and_not(mask).is_empty() {
+            Some(EnumSet { __priv_repr: bits })
+        } else {
+            None
+        }
+    }
+
+    /// Constructs a bitset from a `T::Repr`, ignoring invalid variants.
+    ///
+    /// In order to use this method, the definition of `T` must have the `#[enumset(repr = "…")]`
+    /// annotation.
+    #[inline(always)]
+    pub fn from_repr_truncated(bits: <T as EnumSetTypeWithRepr>::Repr) -> Self
+    where T: EnumSetTypeWithRepr {
+        let mask = Self::all().as_repr();
+        let bits = bits & mask;
+        EnumSet { __priv_repr: bits }
+    }
+
+    /// Constructs a bitset from a `T::Repr`, keeping only the lowest [`EnumSet::variant_count`]
+    /// set bits and ignoring invalid variants.
+    ///
+    /// This differs from [`EnumSet::from_repr_truncated`] in that it caps the number of set bits,
+    /// rather than discarding bits based on their positions. This is useful for protocols where
+    /// only the first few flags sent are meaningful. After the number of bits is capped, any
+    /// remaining bits that do not correspond to an enum variant are ignored.
+    ///
+    /// In order to use this method, the definition of `T` must have the `#[enumset(repr = "…")]`
+    /// annotation.
+    pub fn from_repr_clamped(bits: <T as EnumSetTypeWithRepr>::Repr) -> Self
+    where T: EnumSetTypeWithRepr {
+        let mut remaining = bits;
+        let mut clamped = <T as EnumSetTypeWithRepr>::Repr::empty();
+        for _ in 0..Self::variant_count() {
+            if remaining.is_empty() {
+                break;
+            }
+            let bit = remaining.trailing_zeros();
+            remaining.remove_bit(bit);
+            clamped.add_bit(bit);
+        }
+        Self::from_repr_truncated(clamped)
+    }
+
+    /// Constructs a bitset from a snapshot of a `T::Repr`, ignoring invalid variants.
+    ///
+    /// This is equivalent to [`EnumSet::from_repr_truncated`], and is intended for reading sets
+    /// stored in an atomic integer. The set can be stored using [`EnumSet::as_repr`], and each
+    /// snapshot loaded from the atomic can then be inspected without holding a lock:
+    ///
+    /// ```rust
+    /// # use enumset::*;
+    /// use std::sync::atomic::{AtomicU32, Ordering};
+    ///
+    /// #[derive(EnumSetType, Debug)]
+    /// #[enumset(repr = "u32")]
+    /// enum Flag { A, B, C }
+    ///
+    /// let flags = AtomicU32::new(enum_set!(Flag::A).as_repr());
+    /// flags.fetch_or(EnumSet::only(Flag::C).as_repr(), Ordering::Relaxed);
+    ///
+    /// let snapshot = EnumSet::<Flag>::from_repr_snapshot(flags.load(Ordering::Relaxed));
+    /// assert_eq!(snapshot.iter().collect::<Vec<_>>(), vec![Flag::A, Flag::C]);
+    /// ```
+    ///
+    /// In order to use this method, the definition of `T` must have the `#[enumset(repr = "…")]`
+    /// annotation.
+    #[inline(always)]
+    pub fn from_repr_snapshot(bits: <T as EnumSetTypeWithRepr>::Repr) -> Self
+    where T: EnumSetTypeWithRepr {
+        Self::from_repr_truncated(bits)
+    }
+
+    /// Allows a closure to modify the `T::Repr` representing the elements of this set in place.
+    ///
+    /// Any bits that don't correspond to an enum variant are cleared after the closure returns,
+    /// so this is safe to use even if the closure sets invalid bits. This is useful for passing
+    /// the set to FFI functions that take a pointer to an integer.
+    ///
+    /// In order to use this method, the definition of `T` must have the `#[enumset(repr = "…")]`
+    /// annotation.
+    #[inline(always)]
+    pub fn modify_repr<R, F>(&mut self, f: F) -> R
+    where
+        T: EnumSetTypeWithRepr,
+        F: FnOnce(&mut <T as EnumSetTypeWithRepr>::Repr) -> R,
+    {
+        let result = f(&mut self.__priv_repr);
+        self.__priv_repr = self.__priv_repr & Self::all_bits();
+        result
+    }
+
+    /// Applies a function to the `T::Repr` representing the elements of this set, and constructs
+    /// a new bitset from the result.
+    ///
+    /// If the function returns a value with a bit set that doesn't correspond to an enum variant,
+    /// this method will return `None`. This allows arbitrary bit manipulation without risking an
+    /// invalid set.
+    ///
+    /// In order to use this method, the definition of `T` must have the `#[enumset(repr = "…")]`
+    /// annotation.
+    #[inline(always)]
+    pub fn transform_repr<F>(&self, f: F) -> Option<Self>
+    where
+        T: EnumSetTypeWithRepr,
+        F: FnOnce(<T as EnumSetTypeWithRepr>::Repr) -> <T as EnumSetTypeWithRepr>::Repr,
+    {
+        Self::try_from_repr(f(self.as_repr()))
+    }
+
+    /// Converts a value of a type generated by the `bitflags` crate into a set.
+    ///
+    /// The conversion is done bit by bit, so each flag must use the same bit position as the
+    /// discriminant of the corresponding variant of `T`. If a bit is set that doesn't correspond
+    /// to an enum variant, this method returns `None`.
+    ///
+    /// This method is only available with the `bitflags` feature.
+    #[cfg(feature = "bitflags")]
+    pub fn from_bitflags<F>(flags: F) -> Option<Self>
+    where
+        F: bitflags::Flags,
+        F::Bits: Into<u128>,
+    {
+        Self::try_from_u128(flags.bits().into())
+    }
+
+    /// Converts this set into a value of a type generated by the `bitflags` crate.
+    ///
+    /// The conversion is done bit by bit, so each flag must use the same bit position as the
+    /// discriminant of the corresponding variant of `T`. If this set contains a variant that
+    /// doesn't fit into the underlying type of `F` or doesn't correspond to a flag defined in `F`,
+    /// this method returns `None`.
+    ///
+    /// This method is only available with the `bitflags` feature.
+    #[cfg(feature = "bitflags")]
+    pub fn to_bitflags<F>(&self) -> Option<F>
+    where
+        F: bitflags::Flags,
+        F::Bits: TryFrom<u128>,
+    {
+        let bits = F::Bits::try_from(self.to_u128()).ok()?;
+        F::from_bits(bits)
+    }
+
+    /// Returns a `u128` representing the elements of this set.
+    ///
+    /// Every possible representation fits in a `u128`, so unlike [`EnumSet::as_u128`] and
+    /// [`EnumSet::try_as_u128`], this method can never fail. The original set can be recovered
+    /// with [`EnumSet::try_from_u128`].
+    ///
+    /// The result can be used as a key identifying the contents of a set, e.g. in a `HashMap` or
+    /// `BTreeMap`. Unlike the `Hash` implementation of `EnumSet` itself, it does not depend on the
+    /// width of the underlying representation, so it stays the same if the representation
+    /// changes, e.g. because variants are added to `T`.
+    #[inline(always)]
+    pub fn to_u128(&self) -> u128 {
+        self.__priv_repr.to_u128()
+    }
+}
+
+/// Helper macro for generating conversion functions.
+macro_rules! conversion_impls {
+    (
+        $(for_num!(
+            $underlying:ty, $underlying_str:expr,
+            $from_fn:ident $to_fn:ident $from_fn_opt:ident $to_fn_opt:ident,
+            $from:ident $try_from:ident $from_truncated:ident $from_unchecked:ident,
+            $to:ident $try_to:ident $to_truncated:ident
+        );)*
+    ) => {
+        impl <T : EnumSetType> EnumSet<T> {$(
+            #[doc = "Returns a `"]
+            #[doc = $underlying_str]
+            #[doc = "` representing the elements of this set.\n\nIf the underlying bitset will \
+                     not fit in a `"]
+            #[doc = $underlying_str]
+            #[doc = "` or contains bits that do not correspond to an enum variant, this method \
+                     will panic."]
+            #[inline(always)]
+            pub fn $to(&self) -> $underlying {
+                self.$try_to().expect("Bitset will not fit into this type.")
+            }
+
+            #[doc = "Tries to return a `"]
+            #[doc = $underlying_str]
             #[doc = "` representing the elements of this set.\n\nIf the underlying bitset will \
                      not fit in a `"]
             #[doc = $underlying_str]
@@ -718,6 +2176,28 @@ impl<T: EnumSetType> IntoIterator for EnumSet<T> {
         self.iter()
     }
 }
+/// Iterates the contents of a set behind a reference.
+///
+/// ```rust
+/// # use enumset::*;
+/// #[derive(EnumSetType, Debug)]
+/// enum Enum { A, B, C }
+///
+/// let set = Enum::A | Enum::C;
+/// let mut elements = Vec::new();
+/// for x in &set {
+///     elements.push(x);
+/// }
+/// assert_eq!(elements, vec![Enum::A, Enum::C]);
+/// ```
+impl<T: EnumSetType> IntoIterator for &EnumSet<T> {
+    type Item = T;
+    type IntoIter = EnumSetIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
 impl<T: EnumSetType> Sum for EnumSet<T> {
     fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
         iter.fold(EnumSet::empty(), |a, v| a | v)
@@ -807,70 +2287,459 @@ impl<T: EnumSetType> From<T> for EnumSet<T> {
     }
 }
 
+macro_rules! repr_conversions {
+    ($($repr:ty)*) => {$(
+        /// Converts a set into its `T::Repr`, as with [`EnumSet::as_repr`].
+        ///
+        /// This implementation is only available for enums with the
+        /// `#[enumset(repr = "…")]` annotation.
+        impl<T: EnumSetTypeWithRepr<Repr = $repr>> From<EnumSet<T>> for $repr {
+            #[inline(always)]
+            fn from(set: EnumSet<T>) -> Self {
+                set.as_repr()
+            }
+        }
+
+        /// Converts a `T::Repr` into a set, as with [`EnumSet::try_from_repr`].
+        ///
+        /// This implementation is only available for enums with the
+        /// `#[enumset(repr = "…")]` annotation.
+        impl<T: EnumSetTypeWithRepr<Repr = $repr>> TryFrom<$repr> for EnumSet<T> {
+            type Error = EnumSetReprError<$repr>;
+
+            #[inline(always)]
+            fn try_from(bits: $repr) -> Result<Self, Self::Error> {
+                EnumSet::try_from_repr(bits)
+                    .ok_or_else(|| EnumSetReprError { invalid_bits: bits & !Self::all_bits() })
+            }
+        }
+    )*};
+}
+repr_conversions!(u8 u16 u32 u64 u128);
+
 impl<T: EnumSetType> PartialEq<T> for EnumSet<T> {
     fn eq(&self, other: &T) -> bool {
         self.__priv_repr == EnumSet::only(*other).__priv_repr
     }
 }
-impl<T: EnumSetType + Debug> Debug for EnumSet<T> {
+impl<T: EnumSetType + Display> Display for EnumSet<T> {
+    /// Formats the set as a list of its elements, e.g. `A | B | C | E`.
+    ///
+    /// The empty set is formatted as an empty string. To use a different separator, see
+    /// [`EnumSet::display_with`].
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Display::fmt(&self.display_with(" | "), f)
+    }
+}
+
+impl<T: EnumSetType + FromStr> FromStr for EnumSet<T> {
+    type Err = EnumSetParseError<T::Err>;
+
+    /// Parses a list of elements separated by `|`, e.g. `A | B | C`.
+    ///
+    /// Whitespace around each element is ignored, and each element is parsed with the
+    /// [`FromStr`] implementation of `T`. An empty or all-whitespace string is parsed as the
+    /// empty set.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut set = EnumSet::new();
+        if s.trim().is_empty() {
+            return Ok(set);
+        }
+        for token in s.split('|') {
+            let token = token.trim();
+            if token.is_empty() {
+                return Err(EnumSetParseError::EmptyToken);
+            }
+            set.insert(T::from_str(token).map_err(EnumSetParseError::Element)?);
+        }
+        Ok(set)
+    }
+}
+
+/// The error returned when parsing an [`EnumSet`] from a string fails.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum EnumSetParseError<E> {
+    /// The string contained an empty element, e.g. `A | | B`.
+    EmptyToken,
+    /// An element could not be parsed.
+    Element(E),
+}
+impl<E: Display> Display for EnumSetParseError<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            EnumSetParseError::EmptyToken => f.write_str("empty element in enum set"),
+            EnumSetParseError::Element(e) => write!(f, "invalid element in enum set: {}", e),
+        }
+    }
+}
+
+/// The error returned when converting a `T::Repr` into an [`EnumSet`] with [`TryFrom`] fails.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct EnumSetReprError<R> {
+    /// The bits that were set in the value, but do not correspond to an enum variant.
+    pub invalid_bits: R,
+}
+impl<R: fmt::LowerHex> Display for EnumSetReprError<R> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "bits {:#x} do not correspond to any variant of the enum", self.invalid_bits)
+    }
+}
+
+/// An adapter that displays the elements of an [`EnumSet`] with a custom separator, as returned
+/// by [`EnumSet::display_with`].
+#[derive(Copy, Clone, Debug)]
+pub struct EnumSetDisplay<'a, T: EnumSetType> {
+    set: EnumSet<T>,
+    sep: &'a str,
+}
+impl<'a, T: EnumSetType + Display> Display for EnumSetDisplay<'a, T> {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         let mut is_first = true;
-        f.write_str("EnumSet(")?;
-        for v in self.iter() {
+        for v in self.set.iter() {
             if !is_first {
-                f.write_str(" | ")?;
+                f.write_str(self.sep)?;
             }
             is_first = false;
             v.fmt(f)?;
         }
+        Ok(())
+    }
+}
+
+impl<T: EnumSetType + Debug> Debug for EnumSet<T> {
+    /// Formats the set as a list of its elements, e.g. `EnumSet(A | B | C | E)`.
+    ///
+    /// With the alternate flag (`{:#?}`), runs of elements with consecutive discriminants are
+    /// instead collapsed into ranges, e.g. `EnumSet(A..C | E)`. See [`EnumSet::runs`].
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut is_first = true;
+        f.write_str("EnumSet(")?;
+        if f.alternate() {
+            for (start, end) in self.runs() {
+                if !is_first {
+                    f.write_str(" | ")?;
+                }
+                is_first = false;
+                start.fmt(f)?;
+                if start != end {
+                    f.write_str("..")?;
+                    end.fmt(f)?;
+                }
+            }
+        } else {
+            for v in self.iter() {
+                if !is_first {
+                    f.write_str(" | ")?;
+                }
+                is_first = false;
+                v.fmt(f)?;
+            }
+        }
         f.write_str(")")?;
         Ok(())
     }
 }
 
-#[allow(clippy::derive_hash_xor_eq)] // This impl exists to change trait bounds only.
-impl<T: EnumSetType> Hash for EnumSet<T> {
-    fn hash<H: Hasher>(&self, state: &mut H) {
-        self.__priv_repr.hash(state)
+#[allow(clippy::derived_hash_with_manual_eq)] // This impl exists to change trait bounds only.
+impl<T: EnumSetType> Hash for EnumSet<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.__priv_repr.hash(state)
+    }
+}
+impl<T: EnumSetType> PartialOrd for EnumSet<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl<T: EnumSetType> Ord for EnumSet<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.__priv_repr.cmp(&other.__priv_repr)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: EnumSetType> Serialize for EnumSet<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        T::serialize(*self, serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: EnumSetType> Deserialize<'de> for EnumSet<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        T::deserialize(deserializer)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, T: EnumSetType> arbitrary1::Arbitrary<'a> for EnumSet<T> {
+    fn arbitrary(u: &mut arbitrary1::Unstructured<'a>) -> arbitrary1::Result<Self> {
+        let mut bytes = [0u8; 16];
+        u.fill_buffer(&mut bytes[..Self::arbitrary_byte_len()])?;
+        Ok(EnumSet::from_u128_truncated(u128::from_le_bytes(bytes)))
+    }
+    fn size_hint(_depth: usize) -> (usize, Option<usize>) {
+        let len = Self::arbitrary_byte_len();
+        (len, Some(len))
+    }
+}
+#[cfg(feature = "arbitrary")]
+impl<T: EnumSetType> EnumSet<T> {
+    /// The number of bytes of input needed to cover every valid bit of the set.
+    fn arbitrary_byte_len() -> usize {
+        (Self::bit_width() as usize + 7) / 8
+    }
+}
+
+/// A guard that removes a value from an [`EnumSet`] when dropped, as returned by
+/// [`EnumSet::scoped_insert`].
+#[derive(Debug)]
+pub struct InsertGuard<'a, T: EnumSetType> {
+    set: &'a mut EnumSet<T>,
+    value: T,
+    newly_inserted: bool,
+}
+impl<'a, T: EnumSetType> Deref for InsertGuard<'a, T> {
+    type Target = EnumSet<T>;
+    fn deref(&self) -> &Self::Target {
+        self.set
+    }
+}
+impl<'a, T: EnumSetType> DerefMut for InsertGuard<'a, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.set
+    }
+}
+impl<'a, T: EnumSetType> Drop for InsertGuard<'a, T> {
+    fn drop(&mut self) {
+        if self.newly_inserted {
+            self.set.remove(self.value);
+        }
+    }
+}
+
+/// A description of the bits of an [`EnumSet`], as returned by [`EnumSet::bit_report`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct BitReport {
+    /// The width of the underlying integer of the set, in bits.
+    pub width: u32,
+    /// The positions of the bits that correspond to variants present in the set.
+    ///
+    /// This field is only available with the `alloc` feature. Use
+    /// [`BitReport::iter_present_positions`] otherwise.
+    #[cfg(feature = "alloc")]
+    pub present_positions: Vec<u32>,
+    /// The positions of the bits that correspond to variants absent from the set.
+    ///
+    /// This field is only available with the `alloc` feature. Use
+    /// [`BitReport::iter_absent_valid_positions`] otherwise.
+    #[cfg(feature = "alloc")]
+    pub absent_valid_positions: Vec<u32>,
+    /// The bits set that don't correspond to any variant. This is normally zero.
+    pub invalid_bits_set: u128,
+    present_bits: u128,
+    absent_valid_bits: u128,
+}
+impl BitReport {
+    fn new(width: u32, present_bits: u128, absent_valid_bits: u128, invalid: u128) -> BitReport {
+        BitReport {
+            width,
+            #[cfg(feature = "alloc")]
+            present_positions: BitPositionsIter::new(present_bits).collect(),
+            #[cfg(feature = "alloc")]
+            absent_valid_positions: BitPositionsIter::new(absent_valid_bits).collect(),
+            invalid_bits_set: invalid,
+            present_bits,
+            absent_valid_bits,
+        }
+    }
+
+    /// Iterates the positions of the bits that correspond to variants present in the set, in
+    /// ascending order.
+    pub fn iter_present_positions(&self) -> BitPositionsIter {
+        BitPositionsIter::new(self.present_bits)
+    }
+    /// Iterates the positions of the bits that correspond to variants absent from the set, in
+    /// ascending order.
+    pub fn iter_absent_valid_positions(&self) -> BitPositionsIter {
+        BitPositionsIter::new(self.absent_valid_bits)
+    }
+}
+
+/// The iterator used by [`EnumSet::iter_indices`], [`BitReport::iter_present_positions`] and
+/// [`BitReport::iter_absent_valid_positions`].
+#[derive(Clone, Debug)]
+pub struct BitPositionsIter {
+    bits: u128,
+}
+impl BitPositionsIter {
+    fn new(bits: u128) -> BitPositionsIter {
+        BitPositionsIter { bits }
+    }
+}
+
+impl Iterator for BitPositionsIter {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.bits == 0 {
+            None
+        } else {
+            let bit = self.bits.trailing_zeros();
+            self.bits &= !(1 << bit);
+            Some(bit)
+        }
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let left = self.bits.count_ones() as usize;
+        (left, Some(left))
+    }
+}
+
+impl ExactSizeIterator for BitPositionsIter {}
+
+/// A binary set operation, as used by [`EnumSet::apply_op`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum SetOp {
+    /// Corresponds to [`EnumSet::union`].
+    Union,
+    /// Corresponds to [`EnumSet::intersection`].
+    Intersection,
+    /// Corresponds to [`EnumSet::difference`].
+    Difference,
+    /// Corresponds to [`EnumSet::symmetrical_difference`].
+    SymmetricDifference,
+}
+
+/// Generates random sets, where each variant is present with a probability of 1/2.
+///
+/// This implementation is only available with the `rand` feature.
+#[cfg(feature = "rand")]
+impl<T: EnumSetType> rand::distributions::Distribution<EnumSet<T>>
+    for rand::distributions::Standard
+{
+    fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> EnumSet<T> {
+        EnumSet::from_u128_truncated(rng.gen::<u128>())
+    }
+}
+
+/// The changes required to turn one [`EnumSet`] into another, as returned by [`EnumSet::diff`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct EnumSetDiff<T: EnumSetType> {
+    /// The variants that are present in the target set, but not in the original set.
+    pub to_add: EnumSet<T>,
+    /// The variants that are present in the original set, but not in the target set.
+    pub to_remove: EnumSet<T>,
+}
+impl<T: EnumSetType> EnumSetDiff<T> {
+    /// Returns `true` if the two sets compared were equal.
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.to_add.is_empty() && self.to_remove.is_empty()
+    }
+
+    /// Applies these changes to a set, adding the variants in `to_add` and removing the variants
+    /// in `to_remove`.
+    ///
+    /// Applying the diff to the original set results in the target set.
+    #[inline(always)]
+    pub fn apply(&self, set: &mut EnumSet<T>) {
+        set.remove_all(self.to_remove);
+        set.insert_all(self.to_add);
+    }
+}
+
+/// The iterator used by [`EnumSet`]s.
+#[derive(Clone, Debug)]
+pub struct EnumSetIter<T: EnumSetType> {
+    set: EnumSet<T>,
+}
+impl<T: EnumSetType> EnumSetIter<T> {
+    fn new(set: EnumSet<T>) -> EnumSetIter<T> {
+        EnumSetIter { set }
+    }
+
+    /// Collects the elements that have not yet been yielded by this iterator into a set.
+    ///
+    /// Elements already returned by either [`Iterator::next`] or
+    /// [`DoubleEndedIterator::next_back`] are not included.
+    pub fn collect_set(self) -> EnumSet<T> {
+        self.set
+    }
+}
+
+impl<T: EnumSetType> Iterator for EnumSetIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.set.is_empty() {
+            None
+        } else {
+            let bit = self.set.__priv_repr.trailing_zeros();
+            self.set.__priv_repr.remove_bit(bit);
+            unsafe { Some(T::enum_from_u32(bit)) }
+        }
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let left = self.set.len();
+        (left, Some(left))
+    }
+}
+
+impl<T: EnumSetType> DoubleEndedIterator for EnumSetIter<T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.set.is_empty() {
+            None
+        } else {
+            let bit = T::Repr::WIDTH - 1 - self.set.__priv_repr.leading_zeros();
+            self.set.__priv_repr.remove_bit(bit);
+            unsafe { Some(T::enum_from_u32(bit)) }
+        }
     }
 }
-impl<T: EnumSetType> PartialOrd for EnumSet<T> {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        self.__priv_repr.partial_cmp(&other.__priv_repr)
-    }
+
+/// The iterator used by [`EnumSet::debug_positions`].
+#[derive(Clone, Debug)]
+pub struct EnumSetPositionsIter<T: EnumSetType> {
+    iter: EnumSetIter<T>,
 }
-impl<T: EnumSetType> Ord for EnumSet<T> {
-    fn cmp(&self, other: &Self) -> Ordering {
-        self.__priv_repr.cmp(&other.__priv_repr)
+impl<T: EnumSetType> EnumSetPositionsIter<T> {
+    fn new(set: EnumSet<T>) -> EnumSetPositionsIter<T> {
+        EnumSetPositionsIter { iter: set.iter() }
     }
 }
 
-#[cfg(feature = "serde")]
-impl<T: EnumSetType> Serialize for EnumSet<T> {
-    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
-        T::serialize(*self, serializer)
+impl<T: EnumSetType> Iterator for EnumSetPositionsIter<T> {
+    type Item = (u32, T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|v| (v.enum_into_u32(), v))
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
     }
 }
 
-#[cfg(feature = "serde")]
-impl<'de, T: EnumSetType> Deserialize<'de> for EnumSet<T> {
-    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
-        T::deserialize(deserializer)
+impl<T: EnumSetType> DoubleEndedIterator for EnumSetPositionsIter<T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back().map(|v| (v.enum_into_u32(), v))
     }
 }
 
-/// The iterator used by [`EnumSet`]s.
-#[derive(Clone, Debug)]
-pub struct EnumSetIter<T: EnumSetType> {
-    set: EnumSet<T>,
+impl<T: EnumSetType> ExactSizeIterator for EnumSetPositionsIter<T> {}
+
+/// The iterator used by [`EnumSet::drain`].
+#[derive(Debug)]
+pub struct EnumSetDrain<'a, T: EnumSetType> {
+    set: &'a mut EnumSet<T>,
 }
-impl<T: EnumSetType> EnumSetIter<T> {
-    fn new(set: EnumSet<T>) -> EnumSetIter<T> {
-        EnumSetIter { set }
+impl<'a, T: EnumSetType> EnumSetDrain<'a, T> {
+    fn new(set: &'a mut EnumSet<T>) -> EnumSetDrain<'a, T> {
+        EnumSetDrain { set }
     }
 }
 
-impl<T: EnumSetType> Iterator for EnumSetIter<T> {
+impl<'a, T: EnumSetType> Iterator for EnumSetDrain<'a, T> {
     type Item = T;
 
     fn next(&mut self) -> Option<Self::Item> {
@@ -888,29 +2757,102 @@ impl<T: EnumSetType> Iterator for EnumSetIter<T> {
     }
 }
 
-impl<T: EnumSetType> DoubleEndedIterator for EnumSetIter<T> {
-    fn next_back(&mut self) -> Option<Self::Item> {
+impl<'a, T: EnumSetType> ExactSizeIterator for EnumSetDrain<'a, T> {}
+
+/// The iterator used by [`EnumSet::zip_values`].
+#[derive(Clone, Debug)]
+pub struct EnumSetZipValuesIter<'a, T: EnumSetType, V> {
+    iter: EnumSetIter<T>,
+    values: &'a [V],
+}
+impl<'a, T: EnumSetType, V> EnumSetZipValuesIter<'a, T, V> {
+    fn new(set: EnumSet<T>, values: &'a [V]) -> EnumSetZipValuesIter<'a, T, V> {
+        EnumSetZipValuesIter { iter: set.iter(), values }
+    }
+}
+
+impl<'a, T: EnumSetType, V> Iterator for EnumSetZipValuesIter<'a, T, V> {
+    type Item = (T, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let values = self.values;
+        self.iter
+            .find_map(|v| values.get(v.enum_into_u32() as usize).map(|x| (v, x)))
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.iter.size_hint().1)
+    }
+}
+
+/// The iterator used by [`EnumSet::runs`].
+#[derive(Clone, Debug)]
+pub struct EnumSetRunsIter<T: EnumSetType> {
+    set: EnumSet<T>,
+}
+impl<T: EnumSetType> EnumSetRunsIter<T> {
+    fn new(set: EnumSet<T>) -> EnumSetRunsIter<T> {
+        EnumSetRunsIter { set }
+    }
+}
+
+impl<T: EnumSetType> Iterator for EnumSetRunsIter<T> {
+    type Item = (T, T);
+
+    fn next(&mut self) -> Option<Self::Item> {
         if self.set.is_empty() {
             None
         } else {
-            let bit = T::Repr::WIDTH - 1 - self.set.__priv_repr.leading_zeros();
-            self.set.__priv_repr.remove_bit(bit);
-            unsafe { Some(T::enum_from_u32(bit)) }
+            // The length of the run is the number of trailing ones starting from the lowest set
+            // bit. As every bit in the run is set, each of them corresponds to a variant.
+            let bits = self.set.__priv_repr;
+            let start = bits.trailing_zeros();
+            let len = (!bits.shift_right(start)).trailing_zeros();
+            let end = start + len - 1;
+
+            // Clear the run. Every bit below the run is already clear.
+            self.set.__priv_repr = bits.shift_right(end + 1).shift_left(end + 1);
+
+            unsafe { Some((T::enum_from_u32(start), T::enum_from_u32(end))) }
         }
     }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let left = self.set.len();
+        (if left == 0 { 0 } else { 1 }, Some(left))
+    }
 }
 
 /// The iterator used by [`EnumSet::subsets`].
+///
+/// A set with `n` elements has `2^n` subsets, which does not fit in a `usize` for large sets. In
+/// that case, [`Iterator::size_hint`] and [`ExactSizeIterator::len`] saturate at `usize::MAX`.
 #[derive(Clone, Debug)]
 pub struct EnumSetSubsetIter<T: EnumSetType> {
     set: EnumSet<T>,
     next: EnumSet<T>,
+    next_back: EnumSet<T>,
+    /// The number of subsets between `next` and `next_back`, not counting `next` itself. This is
+    /// one less than the number of subsets remaining, so the full 128-bit case does not overflow.
+    remaining: u128,
     done: bool,
 }
 
 impl<T: EnumSetType> EnumSetSubsetIter<T> {
     fn new(set: EnumSet<T>) -> EnumSetSubsetIter<T> {
-        EnumSetSubsetIter { set, next: EnumSet::empty(), done: false }
+        let remaining = if set.is_empty() {
+            0
+        } else {
+            u128::MAX >> (128 - set.len())
+        };
+        EnumSetSubsetIter { set, next: EnumSet::empty(), next_back: set, remaining, done: false }
+    }
+
+    /// Counts off one yielded subset, marking the iterator as done if it was the last one.
+    fn advance_remaining(&mut self) {
+        if self.remaining == 0 {
+            self.done = true;
+        } else {
+            self.remaining -= 1;
+        }
     }
 }
 
@@ -930,7 +2872,7 @@ impl<T: EnumSetType> Iterator for EnumSetSubsetIter<T> {
             // subset `n`, we can get the next subset by filling in the irrelevant bits `!d` and
             // then adding 1. This causes carry bits to carry through the irrelevant bits of `n`.
             // We then mask away whatever irrelevant bits remain.
-            
+
             // The full expression is `((n | !d) + 1) & d`, although we can improve this.
             // Since `n` is a subset of `d`, it shares no bits with `!d`. This means we can replace
             // the bitwise or with an add, to get `(n + !d + 1) & d`. `!d + 1` is equal to `-d`
@@ -941,22 +2883,410 @@ impl<T: EnumSetType> Iterator for EnumSetSubsetIter<T> {
             // SAFETY: By the invariants of `EnumSet<T>`, `set` only has valid bits set. Since we
             // mask away the clear bits of `set`, `next` must also have only valid bits set.
             self.next.__priv_repr = next;
-            if next.is_empty() {
-                self.done = true;
+            self.advance_remaining();
+
+            Some(current)
+        }
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.done {
+            (0, Some(0))
+        } else {
+            match usize::try_from(self.remaining)
+                .ok()
+                .and_then(|x| x.checked_add(1))
+            {
+                Some(left) => (left, Some(left)),
+                None => (usize::MAX, Some(usize::MAX)),
             }
+        }
+    }
+}
+
+impl<T: EnumSetType> DoubleEndedIterator for EnumSetSubsetIter<T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.done {
+            None
+        } else {
+            let current = self.next_back;
+
+            // This is the carry-rippler trick from `next` in reverse. Subtracting 1 from `n`
+            // borrows through the clear bits of `n`, so the previous subset is `(n - 1) & d`. Once
+            // the front and back meet, `remaining` reaches zero and iteration stops, so this never
+            // wraps around below the empty set.
+            let set = self.set.__priv_repr;
+            let next_back = current.__priv_repr.wrapping_sub(T::Repr::from_u8(1)) & set;
+
+            // SAFETY: As in `next`, `next_back` is masked to the valid bits of `set`.
+            self.next_back.__priv_repr = next_back;
+            self.advance_remaining();
 
             Some(current)
         }
     }
 }
 
+impl<T: EnumSetType> ExactSizeIterator for EnumSetSubsetIter<T> {}
+
+impl<T: EnumSetType> FusedIterator for EnumSetSubsetIter<T> {}
+
+/// The iterator used by [`EnumSet::subsets_with_complement`].
+#[derive(Clone, Debug)]
+pub struct EnumSetSubsetComplementIter<T: EnumSetType> {
+    set: EnumSet<T>,
+    subsets: EnumSetSubsetIter<T>,
+}
+
+impl<T: EnumSetType> EnumSetSubsetComplementIter<T> {
+    fn new(set: EnumSet<T>) -> EnumSetSubsetComplementIter<T> {
+        EnumSetSubsetComplementIter { set, subsets: set.subsets() }
+    }
+}
+
+impl<T: EnumSetType> Iterator for EnumSetSubsetComplementIter<T> {
+    type Item = (EnumSet<T>, EnumSet<T>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let subset = self.subsets.next()?;
+        Some((subset, self.set - subset))
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.subsets.size_hint()
+    }
+}
+
+impl<T: EnumSetType> ExactSizeIterator for EnumSetSubsetComplementIter<T> {}
+
+impl<T: EnumSetType> FusedIterator for EnumSetSubsetComplementIter<T> {}
+
+/// The iterator used by [`EnumSet::subsets_of_size`].
+///
+/// The number of subsets can exceed `usize::MAX` for large sets. In that case,
+/// [`Iterator::size_hint`] and [`ExactSizeIterator::len`] saturate at `usize::MAX`.
+#[derive(Clone, Debug)]
+pub struct EnumSetSizedSubsetIter<T: EnumSetType> {
+    set: EnumSet<T>,
+    next: u128,
+    last: u128,
+    /// The number of subsets not yet yielded. This is at most `C(128, 64)`, which fits in a `u128`.
+    remaining: u128,
+    done: bool,
+}
+
+impl<T: EnumSetType> EnumSetSizedSubsetIter<T> {
+    fn new(set: EnumSet<T>, k: usize) -> EnumSetSizedSubsetIter<T> {
+        // Subsets are represented as `k`-bit combinations of the indices of the elements of
+        // `set`, starting with the lowest `k` indices and ending with the highest `k` indices.
+        let len = set.len();
+        let first = if k == 0 { 0 } else { u128::MAX >> (128 - k.min(128)) };
+        let last = if k == 0 || k > len { 0 } else { first << (len - k) };
+        let remaining = binomial(len as u128, k as u128);
+        EnumSetSizedSubsetIter { set, next: first, last, remaining, done: k > len }
+    }
+}
+
+/// Computes the binomial coefficient `C(n, k)`, which must fit in a `u128`.
+fn binomial(n: u128, k: u128) -> u128 {
+    fn gcd(mut a: u128, mut b: u128) -> u128 {
+        while b != 0 {
+            let r = a % b;
+            a = b;
+            b = r;
+        }
+        a
+    }
+
+    if k > n {
+        return 0;
+    }
+    let k = k.min(n - k);
+    let mut result = 1;
+    for i in 0..k {
+        // `result * (n - i)` is divisible by `i + 1`, but may overflow. Dividing out the common
+        // factor of `result` and `i + 1` first leaves a divisor that must divide `n - i`.
+        let g = gcd(result, i + 1);
+        result = (result / g) * ((n - i) / ((i + 1) / g));
+    }
+    result
+}
+
+impl<T: EnumSetType> Iterator for EnumSetSizedSubsetIter<T> {
+    type Item = EnumSet<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            None
+        } else {
+            let current = self.next;
+            self.remaining -= 1;
+            if current == self.last {
+                self.done = true;
+            } else {
+                // Gosper's hack for finding the next integer with the same number of set bits.
+                // This cannot overflow, as `current` is not the highest combination.
+                let lowest = current & current.wrapping_neg();
+                let ripple = current + lowest;
+                self.next = ripple | (((ripple ^ current) >> 2) / lowest);
+            }
+
+            // Map the combination of indices back onto the bits of the set.
+            let mut result = EnumSet::new();
+            for (i, v) in self.set.iter().enumerate() {
+                if current & (1 << i) != 0 {
+                    result.insert(v);
+                }
+            }
+            Some(result)
+        }
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match usize::try_from(self.remaining) {
+            Ok(left) => (left, Some(left)),
+            Err(_) => (usize::MAX, Some(usize::MAX)),
+        }
+    }
+}
+
+impl<T: EnumSetType> ExactSizeIterator for EnumSetSizedSubsetIter<T> {}
+
+impl<T: EnumSetType> FusedIterator for EnumSetSizedSubsetIter<T> {}
+
 impl<T: EnumSetType> ExactSizeIterator for EnumSetIter<T> {}
 
+impl<T: EnumSetType> FusedIterator for EnumSetIter<T> {}
+
+// The size hint of `EnumSetIter` is always exact, as it is computed from the remaining bits.
+#[cfg(feature = "nightly")]
+unsafe impl<T: EnumSetType> core::iter::TrustedLen for EnumSetIter<T> {}
+
+/// The iterator used by [`EnumSet::tuples_of`].
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug)]
+pub struct EnumSetTuplesIter<T: EnumSetType> {
+    variants: Vec<T>,
+    indices: Vec<usize>,
+    done: bool,
+}
+
+#[cfg(feature = "alloc")]
+impl<T: EnumSetType> EnumSetTuplesIter<T> {
+    fn new(count: usize) -> EnumSetTuplesIter<T> {
+        let variants: Vec<T> = EnumSet::all().iter().collect();
+        let done = variants.is_empty() && count != 0;
+        EnumSetTuplesIter { variants, indices: alloc::vec![0; count], done }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: EnumSetType> Iterator for EnumSetTuplesIter<T> {
+    type Item = Vec<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            None
+        } else {
+            let current = self.indices.iter().map(|&i| self.variants[i]).collect();
+
+            // Increment the indices as a base `variant_count` number, with the last index as the
+            // least significant digit. We are done once every digit has wrapped around.
+            self.done = true;
+            for idx in self.indices.iter_mut().rev() {
+                *idx += 1;
+                if *idx < self.variants.len() {
+                    self.done = false;
+                    break;
+                }
+                *idx = 0;
+            }
+
+            Some(current)
+        }
+    }
+}
+
+/// The iterator used by [`EnumSet::shuffled`].
+#[cfg(feature = "rand")]
+#[derive(Clone, Debug)]
+pub struct EnumSetShuffledIter<T: EnumSetType> {
+    positions: [u8; 128],
+    index: usize,
+    len: usize,
+    _marker: PhantomData<T>,
+}
+
+#[cfg(feature = "rand")]
+impl<T: EnumSetType> EnumSetShuffledIter<T> {
+    fn new<R: rand::Rng + ?Sized>(set: EnumSet<T>, rng: &mut R) -> EnumSetShuffledIter<T> {
+        // Every valid bit position is below 128, so it fits into a `u8`.
+        let mut positions = [0u8; 128];
+        let mut len = 0;
+        let mut bits = set.__priv_repr;
+        while !bits.is_empty() {
+            let bit = bits.trailing_zeros();
+            bits.remove_bit(bit);
+            positions[len] = bit as u8;
+            len += 1;
+        }
+        for i in (1..len).rev() {
+            positions.swap(i, rng.gen_range(0..=i));
+        }
+        EnumSetShuffledIter { positions, index: 0, len, _marker: PhantomData }
+    }
+}
+
+#[cfg(feature = "rand")]
+impl<T: EnumSetType> Iterator for EnumSetShuffledIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index == self.len {
+            None
+        } else {
+            let bit = self.positions[self.index] as u32;
+            self.index += 1;
+            unsafe { Some(T::enum_from_u32(bit)) }
+        }
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let left = self.len - self.index;
+        (left, Some(left))
+    }
+}
+
+#[cfg(feature = "rand")]
+impl<T: EnumSetType> ExactSizeIterator for EnumSetShuffledIter<T> {}
+
+/// Counts how many of the given sets contain each variant of `T`.
+///
+/// The result contains every variant of `T` in order of ascending discriminant, including
+/// variants that are not contained in any of the sets.
+///
+/// This function is only available with the `alloc` feature.
+#[cfg(feature = "alloc")]
+pub fn bit_frequencies<T: EnumSetType>(sets: &[EnumSet<T>]) -> Vec<(T, usize)> {
+    let counts = count_bits(sets);
+    EnumSet::<T>::all()
+        .iter()
+        .map(|v| (v, counts[v.enum_into_u32() as usize]))
+        .collect()
+}
+
+/// Returns the variant contained in the largest number of the given sets, or `None` if every set
+/// is empty.
+///
+/// If several variants are contained in the same number of sets, the one with the lowest
+/// discriminant is returned. Unlike `bit_frequencies`, this does not allocate.
+pub fn most_common<T: EnumSetType>(sets: &[EnumSet<T>]) -> Option<T> {
+    let counts = count_bits(sets);
+    let mut best = None;
+    let mut best_count = 0;
+    for v in EnumSet::<T>::all().iter() {
+        let count = counts[v.enum_into_u32() as usize];
+        if count > best_count {
+            best = Some(v);
+            best_count = count;
+        }
+    }
+    best
+}
+
+/// Counts how many of the given sets have each bit set.
+fn count_bits<T: EnumSetType>(sets: &[EnumSet<T>]) -> [usize; 128] {
+    let mut counts = [0; 128];
+    for set in sets {
+        let mut bits = set.__priv_repr;
+        while !bits.is_empty() {
+            let bit = bits.trailing_zeros();
+            bits.remove_bit(bit);
+            counts[bit as usize] += 1;
+        }
+    }
+    counts
+}
+
+/// Returns a `proptest` strategy that generates arbitrary sets of `T`.
+///
+/// The generated sets are biased towards the empty set and the full set. Sets are shrunk by
+/// removing one element at a time, towards [`EnumSet::empty`].
+///
+/// This function is only available with the `proptest` feature.
+#[cfg(feature = "proptest")]
+pub fn any_enumset<T: EnumSetType + Debug>() -> impl proptest::strategy::Strategy<Value = EnumSet<T>>
+{
+    EnumSetStrategy(PhantomData)
+}
+
+#[cfg(feature = "proptest")]
+#[derive(Debug)]
+struct EnumSetStrategy<T>(PhantomData<T>);
+
+#[cfg(feature = "proptest")]
+impl<T: EnumSetType + Debug> proptest::strategy::Strategy for EnumSetStrategy<T> {
+    type Tree = EnumSetValueTree<T>;
+    type Value = EnumSet<T>;
+
+    fn new_tree(
+        &self,
+        runner: &mut proptest::test_runner::TestRunner,
+    ) -> proptest::strategy::NewTree<Self> {
+        use proptest::prelude::Rng;
+        let rng = runner.rng();
+        let set = match rng.next_u32() % 8 {
+            0 => EnumSet::empty(),
+            1 => EnumSet::all(),
+            _ => {
+                let bits = (rng.next_u64() as u128) << 64 | rng.next_u64() as u128;
+                EnumSet::from_u128_truncated(bits)
+            }
+        };
+        Ok(EnumSetValueTree { set, candidates: set, last_removed: None })
+    }
+}
+
+#[cfg(feature = "proptest")]
+#[derive(Debug)]
+struct EnumSetValueTree<T: EnumSetType> {
+    set: EnumSet<T>,
+    candidates: EnumSet<T>,
+    last_removed: Option<T>,
+}
+
+#[cfg(feature = "proptest")]
+impl<T: EnumSetType + Debug> proptest::strategy::ValueTree for EnumSetValueTree<T> {
+    type Value = EnumSet<T>;
+
+    fn current(&self) -> Self::Value {
+        self.set
+    }
+    fn simplify(&mut self) -> bool {
+        match self.candidates.iter().next() {
+            Some(value) => {
+                self.candidates.remove(value);
+                self.set.remove(value);
+                self.last_removed = Some(value);
+                true
+            }
+            None => false,
+        }
+    }
+    fn complicate(&mut self) -> bool {
+        match self.last_removed.take() {
+            Some(value) => {
+                self.set.insert(value);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
 impl<T: EnumSetType> Extend<T> for EnumSet<T> {
     fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
-        iter.into_iter().for_each(|v| {
-            self.insert(v);
-        });
+        let mut bits = self.__priv_repr;
+        for v in iter {
+            bits.add_bit(v.enum_into_u32());
+        }
+        self.__priv_repr = bits;
     }
 }
 
@@ -968,11 +3298,38 @@ impl<T: EnumSetType> FromIterator<T> for EnumSet<T> {
     }
 }
 
+impl<'a, T: EnumSetType + 'a> Extend<&'a T> for EnumSet<T> {
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied());
+    }
+}
+
+/// Collects a set from references to its elements, such as those yielded by [`slice::iter`].
+///
+/// ```rust
+/// # use enumset::*;
+/// #[derive(EnumSetType, Debug)]
+/// enum Enum { A, B, C, D }
+///
+/// let values = vec![Enum::A, Enum::C, Enum::A];
+/// let set: EnumSet<Enum> = values.iter().collect();
+/// assert_eq!(set, Enum::A | Enum::C);
+/// ```
+impl<'a, T: EnumSetType + 'a> FromIterator<&'a T> for EnumSet<T> {
+    fn from_iter<I: IntoIterator<Item = &'a T>>(iter: I) -> Self {
+        let mut set = EnumSet::default();
+        set.extend(iter);
+        set
+    }
+}
+
 impl<T: EnumSetType> Extend<EnumSet<T>> for EnumSet<T> {
     fn extend<I: IntoIterator<Item = EnumSet<T>>>(&mut self, iter: I) {
-        iter.into_iter().for_each(|v| {
-            self.insert_all(v);
-        });
+        let mut bits = self.__priv_repr;
+        for v in iter {
+            bits = bits | v.__priv_repr;
+        }
+        self.__priv_repr = bits;
     }
 }
 
@@ -984,6 +3341,20 @@ impl<T: EnumSetType> FromIterator<EnumSet<T>> for EnumSet<T> {
     }
 }
 
+impl<'a, T: EnumSetType + 'a> Extend<&'a EnumSet<T>> for EnumSet<T> {
+    fn extend<I: IntoIterator<Item = &'a EnumSet<T>>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied());
+    }
+}
+
+impl<'a, T: EnumSetType + 'a> FromIterator<&'a EnumSet<T>> for EnumSet<T> {
+    fn from_iter<I: IntoIterator<Item = &'a EnumSet<T>>>(iter: I) -> Self {
+        let mut set = EnumSet::default();
+        set.extend(iter);
+        set
+    }
+}
+
 /// Creates a EnumSet literal, which can be used in const contexts.
 ///
 /// The syntax used is `enum_set!(Type::A | Type::B | Type::C)`. Each variant must be of the same
@@ -1,6 +1,8 @@
+use crate::EnumSetType;
 use core::convert::TryInto;
 use core::fmt::Debug;
 use core::hash::Hash;
+use core::mem::size_of;
 use core::ops::*;
 
 /// A trait marking valid underlying bitset storage types and providing the
@@ -34,6 +36,9 @@ pub trait EnumSetTypeRepr :
 
     fn and_not(&self, other: Self) -> Self;
     fn wrapping_sub(&self, other: Self) -> Self;
+    fn reverse_bits(&self) -> Self;
+    fn shift_left(&self, bits: u32) -> Self;
+    fn shift_right(&self, bits: u32) -> Self;
 
     fn from_u8(v: u8) -> Self;
     fn from_u16(v: u16) -> Self;
@@ -113,6 +118,21 @@ macro_rules! prim {
                 (*self).wrapping_sub(other)
             }
 
+            #[inline(always)]
+            fn reverse_bits(&self) -> Self {
+                (*self).reverse_bits()
+            }
+
+            #[inline(always)]
+            fn shift_left(&self, bits: u32) -> Self {
+                (*self).checked_shl(bits).unwrap_or(0)
+            }
+
+            #[inline(always)]
+            fn shift_right(&self, bits: u32) -> Self {
+                (*self).checked_shr(bits).unwrap_or(0)
+            }
+
             #[inline(always)]
             fn count_remaining_ones(&self, cursor: u32) -> usize {
                 let left_mask = !((1 as $name)
@@ -229,3 +249,84 @@ prim!(u16, 16);
 prim!(u32, 32);
 prim!(u64, 64);
 prim!(u128, 128);
+
+/// Converts a repr into a `u128` in a `const` context.
+///
+/// Trait methods cannot be called in `const fn`s, so this reads the repr as the unsigned integer
+/// of the same size instead. Every type implementing `EnumSetTypeRepr` is such an integer.
+#[inline(always)]
+pub const fn const_repr_to_u128<R: EnumSetTypeRepr>(repr: R) -> u128 {
+    union Cast<R: Copy> {
+        repr: R,
+        v8: u8,
+        v16: u16,
+        v32: u32,
+        v64: u64,
+        v128: u128,
+    }
+    let cast = Cast { repr };
+    unsafe {
+        match size_of::<R>() {
+            1 => cast.v8 as u128,
+            2 => cast.v16 as u128,
+            4 => cast.v32 as u128,
+            8 => cast.v64 as u128,
+            16 => cast.v128,
+            _ => panic!("unsupported repr size"),
+        }
+    }
+}
+
+/// Converts a `u128` into a repr in a `const` context, truncating any bits that do not fit.
+#[inline(always)]
+pub const fn const_repr_from_u128<R: EnumSetTypeRepr>(bits: u128) -> R {
+    union Cast<R: Copy> {
+        repr: R,
+        v8: u8,
+        v16: u16,
+        v32: u32,
+        v64: u64,
+        v128: u128,
+    }
+    unsafe {
+        match size_of::<R>() {
+            1 => Cast { v8: bits as u8 }.repr,
+            2 => Cast { v16: bits as u16 }.repr,
+            4 => Cast { v32: bits as u32 }.repr,
+            8 => Cast { v64: bits as u64 }.repr,
+            16 => Cast { v128: bits }.repr,
+            _ => panic!("unsupported repr size"),
+        }
+    }
+}
+
+/// Converts an enum into its bit position in a `const` context.
+///
+/// This reads the discriminant directly from the memory of the enum. As this relies on the layout
+/// of the enum, `#[derive(EnumSetType)]` checks at compile time that the result is the same as
+/// `enum_into_u32` for every variant.
+#[inline(always)]
+pub const fn const_enum_into_u32<T: EnumSetType>(value: T) -> u32 {
+    union Cast<T: Copy> {
+        value: T,
+        v8: u8,
+        v16: u16,
+        v32: u32,
+        v64: u64,
+        v128: u128,
+    }
+    let cast = Cast { value };
+    unsafe {
+        match size_of::<T>() {
+            // Enums with a single variant are zero-sized, so the only valid bit is the one for
+            // that variant.
+            0 => const_repr_to_u128(T::ALL_BITS).trailing_zeros(),
+            1 => cast.v8 as u32,
+            2 => cast.v16 as u32,
+            4 => cast.v32,
+            8 => cast.v64 as u32,
+            16 => cast.v128 as u32,
+            _ => panic!("unsupported enum size"),
+        }
+    }
+}
@@ -0,0 +1,102 @@
+#![cfg(feature = "alloc")]
+#![allow(dead_code)]
+
+use enumset::*;
+
+#[derive(EnumSetType, Debug)]
+pub enum EmptyEnum { }
+
+#[derive(EnumSetType, Debug)]
+pub enum Enum3 {
+    A, B, C,
+}
+
+#[derive(EnumSetType, Debug)]
+pub enum SparseEnum {
+    A = 0xA, B = 20, C = 30,
+}
+
+#[test]
+fn tuples_of_test() {
+    let tuples: Vec<_> = EnumSet::<Enum3>::tuples_of(2).collect();
+    assert_eq!(tuples.len(), 9);
+    assert_eq!(tuples[0], vec![Enum3::A, Enum3::A]);
+    assert_eq!(tuples[1], vec![Enum3::A, Enum3::B]);
+    assert_eq!(tuples[3], vec![Enum3::B, Enum3::A]);
+    assert_eq!(tuples[8], vec![Enum3::C, Enum3::C]);
+
+    let sparse: Vec<_> = EnumSet::<SparseEnum>::tuples_of(3).collect();
+    assert_eq!(sparse.len(), 27);
+    assert_eq!(sparse[5], vec![SparseEnum::A, SparseEnum::B, SparseEnum::C]);
+}
+
+#[test]
+fn tuples_of_edge_cases() {
+    assert_eq!(EnumSet::<Enum3>::tuples_of(0).collect::<Vec<_>>(), vec![Vec::<Enum3>::new()]);
+    assert_eq!(EnumSet::<EmptyEnum>::tuples_of(0).count(), 1);
+    assert_eq!(EnumSet::<EmptyEnum>::tuples_of(2).count(), 0);
+}
+
+#[test]
+fn to_sorted_vec_by_key_test() {
+    let set = Enum3::A | Enum3::B | Enum3::C;
+    let names = |v: &Enum3| match v {
+        Enum3::A => "zebra",
+        Enum3::B => "apple",
+        Enum3::C => "mango",
+    };
+    assert_eq!(set.to_sorted_vec_by_key(names), vec![Enum3::B, Enum3::C, Enum3::A]);
+    assert_eq!(set.to_sorted_vec_by_key(|_| 0), vec![Enum3::A, Enum3::B, Enum3::C]);
+    assert!(EnumSet::<Enum3>::empty().to_sorted_vec_by_key(names).is_empty());
+}
+
+#[test]
+fn map_to_values_test() {
+    let set = Enum3::A | Enum3::C;
+    let mut calls = 0;
+    let values = set.map_to_values(|v| {
+        calls += 1;
+        format!("{:?}", v)
+    });
+    assert_eq!(values, vec![(Enum3::A, "A".to_string()), (Enum3::C, "C".to_string())]);
+    assert_eq!(calls, 2);
+    assert!(EnumSet::<Enum3>::empty().map_to_values(|v| v).is_empty());
+}
+
+#[test]
+fn to_state_pairs_test() {
+    assert_eq!(
+        (Enum3::A | Enum3::C).to_state_pairs(),
+        vec![("A", true), ("B", false), ("C", true)],
+    );
+    assert_eq!(
+        EnumSet::only(SparseEnum::B).to_state_pairs(),
+        vec![("A", false), ("B", true), ("C", false)],
+    );
+    assert!(EnumSet::<EmptyEnum>::empty().to_state_pairs().is_empty());
+}
+
+#[test]
+fn bit_frequencies_test() {
+    let sets = [Enum3::A | Enum3::B, EnumSet::only(Enum3::A), EnumSet::empty()];
+    assert_eq!(bit_frequencies(&sets), vec![(Enum3::A, 2), (Enum3::B, 1), (Enum3::C, 0)]);
+    assert_eq!(
+        bit_frequencies::<Enum3>(&[]),
+        vec![(Enum3::A, 0), (Enum3::B, 0), (Enum3::C, 0)],
+    );
+
+    let sparse = [SparseEnum::B | SparseEnum::C, EnumSet::only(SparseEnum::C)];
+    assert_eq!(
+        bit_frequencies(&sparse),
+        vec![(SparseEnum::A, 0), (SparseEnum::B, 1), (SparseEnum::C, 2)],
+    );
+}
+
+#[test]
+fn bit_report_test() {
+    let report = (SparseEnum::A | SparseEnum::C).bit_report();
+    assert_eq!(report.width, 32);
+    assert_eq!(report.present_positions, vec![10, 30]);
+    assert_eq!(report.absent_valid_positions, vec![20]);
+    assert_eq!(report.invalid_bits_set, 0);
+}
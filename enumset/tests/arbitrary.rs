@@ -0,0 +1,69 @@
+#![cfg(feature = "arbitrary")]
+
+use arbitrary1::{Arbitrary, Unstructured};
+use enumset::*;
+
+#[derive(EnumSetType, Debug)]
+#[enumset(repr = "u16")]
+pub enum SparseEnum {
+    A = 1, B = 3, C = 5, D = 9, E = 10,
+}
+
+#[derive(EnumSetType, Debug)]
+pub enum LargeEnum {
+    A = 10, B = 50, C = 127,
+}
+
+#[derive(EnumSetType, Debug)]
+pub enum EmptyEnum {}
+
+fn input() -> Vec<u8> {
+    (0..4096u32).map(|x| (x.wrapping_mul(2654435761) >> 13) as u8).collect()
+}
+
+#[test]
+fn arbitrary_sets_are_valid() {
+    let data = input();
+    let mut u = Unstructured::new(&data);
+    let mut seen = EnumSet::<SparseEnum>::new();
+    for _ in 0..200 {
+        let set = EnumSet::<SparseEnum>::arbitrary(&mut u).unwrap();
+        assert_eq!(EnumSet::try_from_repr(set.as_repr()), Some(set));
+        seen |= set;
+    }
+    assert_eq!(seen, EnumSet::all());
+
+    for _ in 0..200 {
+        let set = EnumSet::<LargeEnum>::arbitrary(&mut u).unwrap();
+        assert!(set.is_valid());
+    }
+}
+
+#[test]
+fn arbitrary_sets_on_empty_input() {
+    let mut u = Unstructured::new(&[]);
+    assert_eq!(EnumSet::<SparseEnum>::arbitrary(&mut u).unwrap(), EnumSet::empty());
+    let mut u = Unstructured::new(&[]);
+    assert_eq!(EnumSet::<EmptyEnum>::arbitrary(&mut u).unwrap(), EnumSet::empty());
+}
+
+#[test]
+fn arbitrary_size_hint() {
+    assert_eq!(EnumSet::<SparseEnum>::size_hint(0), (2, Some(2)));
+    assert_eq!(EnumSet::<LargeEnum>::size_hint(0), (16, Some(16)));
+    assert_eq!(EnumSet::<EmptyEnum>::size_hint(0), (0, Some(0)));
+}
+
+#[test]
+fn arbitrary_variants() {
+    let data = input();
+    let mut u = Unstructured::new(&data);
+    let mut seen = EnumSet::<LargeEnum>::new();
+    for _ in 0..100 {
+        seen.insert(LargeEnum::arbitrary(&mut u).unwrap());
+    }
+    assert_eq!(seen, EnumSet::all());
+
+    let mut u = Unstructured::new(&data);
+    assert!(EmptyEnum::arbitrary(&mut u).is_err());
+}
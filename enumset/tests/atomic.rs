@@ -0,0 +1,54 @@
+#![cfg(feature = "atomic")]
+
+use enumset::*;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::thread;
+
+#[derive(EnumSetType, Debug)]
+#[enumset(repr = "u8")]
+enum Enum8 {
+    A, B, C, D, E, F, G, H,
+}
+
+#[derive(EnumSetType, Debug)]
+#[enumset(repr = "u64")]
+enum Enum64 {
+    A, B, C, D, E, F, G, H,
+}
+
+#[test]
+fn basic_ops() {
+    let set = AtomicEnumSet::new(Enum8::A | Enum8::C);
+    assert!(set.contains(Enum8::A, Ordering::SeqCst));
+    assert!(!set.contains(Enum8::B, Ordering::SeqCst));
+
+    assert!(set.insert(Enum8::B, Ordering::SeqCst));
+    assert!(!set.insert(Enum8::B, Ordering::SeqCst));
+    assert!(set.remove(Enum8::A, Ordering::SeqCst));
+    assert!(!set.remove(Enum8::A, Ordering::SeqCst));
+    assert_eq!(set.load(Ordering::SeqCst), Enum8::B | Enum8::C);
+
+    assert_eq!(set.insert_all(Enum8::D | Enum8::E, Ordering::SeqCst), Enum8::B | Enum8::C);
+    assert_eq!(set.remove_all(Enum8::B | Enum8::E, Ordering::SeqCst),
+               Enum8::B | Enum8::C | Enum8::D | Enum8::E);
+
+    assert_eq!(set.swap(EnumSet::only(Enum8::H), Ordering::SeqCst), Enum8::C | Enum8::D);
+    set.store(Enum8::F | Enum8::G, Ordering::SeqCst);
+    assert_eq!(format!("{:?}", set), "EnumSet(F | G)");
+    assert_eq!(set.into_inner(), Enum8::F | Enum8::G);
+    assert!(AtomicEnumSet::<Enum8>::default().load(Ordering::SeqCst).is_empty());
+}
+
+#[test]
+fn concurrent_inserts() {
+    let set = Arc::new(AtomicEnumSet::<Enum64>::default());
+    let threads: Vec<_> = EnumSet::<Enum64>::all().iter().map(|v| {
+        let set = set.clone();
+        thread::spawn(move || set.insert(v, Ordering::Relaxed))
+    }).collect();
+    for thread in threads {
+        assert!(thread.join().unwrap());
+    }
+    assert_eq!(set.load(Ordering::SeqCst), EnumSet::all());
+}
@@ -0,0 +1,42 @@
+#![cfg(feature = "bitflags")]
+
+use enumset::*;
+
+#[derive(EnumSetType, Debug)]
+pub enum Enum {
+    A, B, C, D = 6,
+}
+
+bitflags::bitflags! {
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+    pub struct Flags: u8 {
+        const A = 1 << 0;
+        const B = 1 << 1;
+        const C = 1 << 2;
+        const D = 1 << 6;
+    }
+}
+
+bitflags::bitflags! {
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+    pub struct PartialFlags: u8 {
+        const A = 1 << 0;
+        const B = 1 << 1;
+    }
+}
+
+#[test]
+fn from_bitflags() {
+    assert_eq!(EnumSet::from_bitflags(Flags::A | Flags::D), Some(Enum::A | Enum::D));
+    assert_eq!(EnumSet::<Enum>::from_bitflags(Flags::all()), Some(EnumSet::all()));
+    assert_eq!(EnumSet::<Enum>::from_bitflags(Flags::empty()), Some(EnumSet::empty()));
+    assert_eq!(EnumSet::<Enum>::from_bitflags(Flags::from_bits_retain(1 << 3)), None);
+}
+
+#[test]
+fn to_bitflags() {
+    assert_eq!((Enum::B | Enum::C).to_bitflags(), Some(Flags::B | Flags::C));
+    assert_eq!(EnumSet::<Enum>::all().to_bitflags(), Some(Flags::all()));
+    assert_eq!((Enum::A | Enum::B).to_bitflags(), Some(PartialFlags::all()));
+    assert_eq!((Enum::A | Enum::D).to_bitflags::<PartialFlags>(), None);
+}
@@ -0,0 +1,26 @@
+#![cfg(feature = "nightly")]
+#![feature(trusted_len)]
+
+use core::iter::TrustedLen;
+use enumset::*;
+
+#[derive(EnumSetType, Debug)]
+pub enum Enum {
+    A, B, C, D, E, F, G, H,
+}
+
+fn assert_trusted_len<I: TrustedLen>(iter: I) -> I {
+    iter
+}
+
+#[test]
+fn iter_is_trusted_len() {
+    let set = Enum::A | Enum::C | Enum::H;
+    let mut iter = assert_trusted_len(set.iter());
+    assert_eq!(iter.size_hint(), (3, Some(3)));
+    iter.next();
+    assert_eq!(iter.size_hint(), (2, Some(2)));
+
+    let vec: Vec<_> = assert_trusted_len(set.into_iter()).collect();
+    assert_eq!(vec, vec![Enum::A, Enum::C, Enum::H]);
+}
@@ -114,15 +114,57 @@ macro_rules! test_enum {
         const CONST_SET: EnumSet<$e> = enum_set!($e::A | $e::C);
         const CONST_1_SET: EnumSet<$e> = enum_set!($e::A);
         const EMPTY_SET: EnumSet<$e> = enum_set!();
+        const SINGLE_SET: EnumSet<$e> = EnumSet::single($e::C);
         #[test]
         fn const_set() {
             assert_eq!(CONST_SET.len(), 2);
             assert_eq!(CONST_1_SET.len(), 1);
+            assert_eq!(SINGLE_SET, EnumSet::only($e::C));
             assert!(CONST_SET.contains($e::A));
             assert!(CONST_SET.contains($e::C));
             assert!(EMPTY_SET.is_empty());
         }
 
+        #[test]
+        fn all_except_test() {
+            const EXCLUDED: EnumSet<$e> = enum_set!($e::A | $e::C);
+            const ALL_EXCEPT: EnumSet<$e> = EnumSet::all_except_const(EXCLUDED);
+            assert_eq!(ALL_EXCEPT, EnumSet::all() - EXCLUDED);
+            assert_eq!(EnumSet::all_except(EXCLUDED), ALL_EXCEPT);
+            assert_eq!(EnumSet::<$e>::all_except(EnumSet::empty()), EnumSet::all());
+            assert_eq!(EnumSet::<$e>::all_except(EnumSet::all()), EnumSet::empty());
+        }
+
+        #[test]
+        fn const_ops_test() {
+            const A: EnumSet<$e> = enum_set!($e::A | $e::B | $e::C);
+            const B: EnumSet<$e> = enum_set!($e::C | $e::D);
+            const UNION: EnumSet<$e> = A.const_union(B);
+            const INTERSECTION: EnumSet<$e> = A.const_intersection(B);
+            const DIFFERENCE: EnumSet<$e> = A.const_difference(B);
+            const COMPLEMENT: EnumSet<$e> = A.const_complement();
+            assert_eq!(UNION, A | B);
+            assert_eq!(INTERSECTION, A & B);
+            assert_eq!(DIFFERENCE, A - B);
+            assert_eq!(COMPLEMENT, !A);
+        }
+
+        #[test]
+        fn const_set_operations() {
+            const A: EnumSet<$e> = enum_set!($e::A | $e::B | $e::C);
+            const B: EnumSet<$e> = enum_set!($e::C | $e::D);
+            const UNION: EnumSet<$e> = A.union(B);
+            const INTERSECTION: EnumSet<$e> = A.intersection(B);
+            const DIFFERENCE: EnumSet<$e> = A.difference(B);
+            const SYMMETRICAL_DIFFERENCE: EnumSet<$e> = A.symmetrical_difference(B);
+            const COMPLEMENT: EnumSet<$e> = A.complement();
+            assert_eq!(UNION, $e::A | $e::B | $e::C | $e::D);
+            assert_eq!(INTERSECTION, $e::C);
+            assert_eq!(DIFFERENCE, $e::A | $e::B);
+            assert_eq!(SYMMETRICAL_DIFFERENCE, $e::A | $e::B | $e::D);
+            assert_eq!(COMPLEMENT, EnumSet::all() - A);
+        }
+
         #[test]
         fn basic_add_remove() {
             let mut set = EnumSet::new();
@@ -143,6 +185,170 @@ macro_rules! test_enum {
             assert!(set.is_empty());
         }
 
+        #[test]
+        fn counts_by_masks_test() {
+            let set = $e::A | $e::B | $e::C | $e::E;
+            let masks = [$e::A | $e::B, $e::B | $e::D, EnumSet::all(), EnumSet::empty()];
+            assert_eq!(set.counts_by_masks(masks), [2, 1, 4, 0]);
+            assert_eq!(set.counts_by_masks([]), []);
+        }
+
+        #[test]
+        fn bit_test() {
+            let set = $e::A | $e::C;
+            assert!(set.bit($e::A as u32));
+            assert!(set.bit($e::C as u32));
+            assert!(!set.bit($e::B as u32));
+            assert!(!set.bit(EnumSet::<$e>::bit_width()));
+            assert!(!set.bit(u32::MAX));
+            assert!(!EnumSet::<$e>::all().bit(200));
+        }
+
+        #[test]
+        fn into_single_test() {
+            assert_eq!(EnumSet::only($e::C).into_single(), Ok($e::C));
+            assert_eq!(($e::A | $e::C).into_single(), Err($e::A | $e::C));
+            assert_eq!(EnumSet::<$e>::empty().into_single(), Err(EnumSet::empty()));
+        }
+
+        #[test]
+        fn first_last_test() {
+            assert_eq!(EnumSet::<$e>::empty().first(), None);
+            assert_eq!(EnumSet::<$e>::empty().last(), None);
+            assert_eq!(EnumSet::only($e::C).first(), Some($e::C));
+            assert_eq!(EnumSet::only($e::C).last(), Some($e::C));
+            assert_eq!(($e::B | $e::D | $e::E).first(), Some($e::B));
+            assert_eq!(($e::B | $e::D | $e::E).last(), Some($e::E));
+            let all = EnumSet::<$e>::all();
+            assert_eq!(all.first(), all.iter().next());
+            assert_eq!(all.last(), all.iter().next_back());
+        }
+
+        #[test]
+        fn toggle_test() {
+            let original = $e::A | $e::C;
+            let mut set = original;
+            assert!(set.toggle($e::B));
+            assert_eq!(set, $e::A | $e::B | $e::C);
+            assert!(!set.toggle($e::C));
+            assert_eq!(set, $e::A | $e::B);
+            assert!(!set.toggle($e::B));
+            assert!(set.toggle($e::C));
+            assert_eq!(set, original);
+        }
+
+        #[test]
+        fn toggle_all_test() {
+            let mut set = $e::A | $e::B | $e::C;
+            let other = $e::B | $e::C | $e::D;
+            set.toggle_all(other);
+            assert_eq!(set, $e::A | $e::D);
+            assert_eq!(set, ($e::A | $e::B | $e::C) ^ other);
+            set.toggle_all(other);
+            assert_eq!(set, $e::A | $e::B | $e::C);
+            set.toggle_all(EnumSet::empty());
+            assert_eq!(set, $e::A | $e::B | $e::C);
+        }
+
+        #[test]
+        fn pop_first_last_test() {
+            let mut set = $e::A | $e::B | $e::C | $e::D | $e::E;
+            assert_eq!(set.pop_first(), Some($e::A));
+            assert_eq!(set.pop_last(), Some($e::E));
+            assert_eq!(set.pop_last(), Some($e::D));
+            assert_eq!(set, $e::B | $e::C);
+            assert_eq!(set.pop_first(), Some($e::B));
+            assert_eq!(set.pop_last(), Some($e::C));
+            assert_eq!(set, EnumSet::empty());
+            assert_eq!(set.pop_first(), None);
+            assert_eq!(set.pop_last(), None);
+            assert_eq!(set, EnumSet::empty());
+        }
+
+        #[test]
+        fn nth_test() {
+            let set = $e::A | $e::C | $e::D | $e::F;
+            for i in 0..6 {
+                assert_eq!(set.nth(i), set.iter().nth(i));
+            }
+            assert_eq!(set.nth(1), Some($e::C));
+            assert_eq!(set.nth(4), None);
+            assert_eq!(set.nth(usize::MAX), None);
+            assert_eq!(EnumSet::<$e>::empty().nth(0), None);
+            let all = EnumSet::<$e>::all();
+            assert_eq!(all.nth(all.len() - 1), all.last());
+            assert_eq!(all.nth(all.len()), None);
+        }
+
+        #[test]
+        fn nth_position_of_inverse_test() {
+            let set = $e::A | $e::C | $e::D | $e::F;
+            for (i, value) in set.iter().enumerate() {
+                assert_eq!(set.position_of(value), Some(i));
+                assert_eq!(set.nth(i), Some(value));
+                assert_eq!(set.position_of(value).and_then(|i| set.nth(i)), Some(value));
+            }
+            assert_eq!(set.position_of($e::B), None);
+        }
+
+        #[test]
+        fn first_last_absent_test() {
+            let all = EnumSet::<$e>::all();
+            assert_eq!(EnumSet::<$e>::empty().first_absent(), all.iter().next());
+            assert_eq!(EnumSet::<$e>::empty().last_absent(), all.iter().next_back());
+            assert_eq!((all - $e::C - $e::E).first_absent(), Some($e::C));
+            assert_eq!((all - $e::E).last_absent(), Some($e::E));
+            assert_eq!((all - $e::C - $e::E).last_absent(), Some($e::E));
+            assert_eq!(all.first_absent(), None);
+            assert_eq!(all.last_absent(), None);
+        }
+
+        #[test]
+        fn try_remove_test() {
+            let mut set = $e::A | $e::B;
+            assert_eq!(set.try_remove($e::A), Ok(()));
+            assert_eq!(set, $e::B);
+            assert_eq!(set.try_remove($e::A), Err($e::A));
+            assert_eq!(set, $e::B);
+        }
+
+        #[test]
+        fn intersect_with_changed_test() {
+            let mut set = $e::A | $e::B | $e::C;
+            assert!(set.intersect_with_changed($e::A | $e::C | $e::D));
+            assert_eq!(set, $e::A | $e::C);
+            assert!(!set.intersect_with_changed($e::A | $e::C | $e::D));
+            assert_eq!(set, $e::A | $e::C);
+            assert!(set.intersect_with_changed(EnumSet::empty()));
+            assert!(set.is_empty());
+        }
+
+        #[test]
+        fn try_insert_all_test() {
+            let mut set = $e::A | $e::B;
+            assert_eq!(set.try_insert_all($e::C | $e::D), Ok(()));
+            assert_eq!(set, $e::A | $e::B | $e::C | $e::D);
+            assert_eq!(set.try_insert_all($e::B | $e::D | $e::E), Err($e::B | $e::D));
+            assert_eq!(set, $e::A | $e::B | $e::C | $e::D);
+            assert_eq!(set.try_insert_all(EnumSet::empty()), Ok(()));
+        }
+
+        #[test]
+        fn scoped_insert_test() {
+            let mut set = EnumSet::only($e::A);
+            {
+                let mut guard = set.scoped_insert($e::B);
+                assert_eq!(*guard, $e::A | $e::B);
+                guard.insert($e::C);
+            }
+            assert_eq!(set, $e::A | $e::C);
+            {
+                let guard = set.scoped_insert($e::A);
+                assert_eq!(*guard, $e::A | $e::C);
+            }
+            assert_eq!(set, $e::A | $e::C);
+        }
+
         #[test]
         fn already_present_element() {
             let mut set = EnumSet::new();
@@ -201,6 +407,432 @@ macro_rules! test_enum {
             assert_eq!(set, set_5);
         }
 
+        #[test]
+        fn try_from_iter_test() {
+            let deny_b = |v: &$e| if *v == $e::B { Err(*v) } else { Ok(()) };
+            let set = EnumSet::try_from_iter(vec![$e::A, $e::C, $e::A], deny_b);
+            assert_eq!(set, Ok($e::A | $e::C));
+            let set = EnumSet::try_from_iter(vec![$e::A, $e::B, $e::C], deny_b);
+            assert_eq!(set, Err($e::B));
+        }
+
+        #[test]
+        fn extend_test() {
+            let mut set = EnumSet::only($e::A);
+            set.extend_from_slice(&[$e::B, $e::D, $e::B]);
+            assert_eq!(set, $e::A | $e::B | $e::D);
+            set.extend_from_slice(&[]);
+            assert_eq!(set, $e::A | $e::B | $e::D);
+
+            set.extend(vec![$e::A, $e::E]);
+            assert_eq!(set, $e::A | $e::B | $e::D | $e::E);
+            set.extend(vec![$e::F | $e::G, EnumSet::only($e::C)]);
+            assert_eq!(set, $e::A | $e::B | $e::C | $e::D | $e::E | $e::F | $e::G);
+        }
+
+        #[test]
+        fn intersect_all_test() {
+            let sets = vec![$e::A | $e::B | $e::C, $e::B | $e::C | $e::D, $e::B | $e::E];
+            assert_eq!(EnumSet::intersect_all(sets.clone()), $e::B);
+            assert_eq!(EnumSet::intersect_all(sets.into_iter().take(2)), $e::B | $e::C);
+            assert_eq!(EnumSet::intersect_all(vec![$e::A | $e::B, $e::C | $e::D]), EnumSet::empty());
+            assert_eq!(EnumSet::<$e>::intersect_all(None), EnumSet::all());
+        }
+
+        #[test]
+        fn extend_from_refs_test() {
+            let values = vec![$e::B, $e::D, $e::B];
+            let set: EnumSet<$e> = values.iter().collect();
+            assert_eq!(set, $e::B | $e::D);
+
+            let mut set = EnumSet::only($e::A);
+            set.extend(values.iter());
+            set.extend(&[$e::E]);
+            assert_eq!(set, $e::A | $e::B | $e::D | $e::E);
+            assert_eq!(<[$e; 0]>::default().iter().collect::<EnumSet<$e>>(), EnumSet::empty());
+        }
+
+        #[test]
+        fn extend_from_set_refs_test() {
+            let sets: &[EnumSet<$e>] = &[$e::A | $e::B, EnumSet::only($e::D), EnumSet::empty()];
+            let set: EnumSet<$e> = sets.iter().collect();
+            assert_eq!(set, $e::A | $e::B | $e::D);
+
+            let mut set = EnumSet::only($e::E);
+            set.extend(sets);
+            assert_eq!(set, $e::A | $e::B | $e::D | $e::E);
+            set.extend(&[] as &[EnumSet<$e>]);
+            assert_eq!(set, $e::A | $e::B | $e::D | $e::E);
+        }
+
+        #[test]
+        fn drain_test() {
+            let mut set = $e::A | $e::C | $e::E;
+            let drained: Vec<$e> = set.drain().collect();
+            assert_eq!(drained, vec![$e::A, $e::C, $e::E]);
+            assert!(set.is_empty());
+
+            let mut set = $e::A | $e::C | $e::E;
+            {
+                let mut drain = set.drain();
+                assert_eq!(drain.len(), 3);
+                assert_eq!(drain.next(), Some($e::A));
+                assert_eq!(drain.len(), 2);
+            }
+            assert_eq!(set, $e::C | $e::E);
+        }
+
+        #[test]
+        fn ref_into_iter_test() {
+            fn collect<I: IntoIterator<Item = $e>>(iter: I) -> Vec<$e> {
+                iter.into_iter().collect()
+            }
+            let set = $e::A | $e::C | $e::E;
+            assert_eq!(collect(&set), vec![$e::A, $e::C, $e::E]);
+            let mut count = 0;
+            for _ in &set {
+                count += 1;
+            }
+            assert_eq!(count, 3);
+        }
+
+        #[test]
+        fn collect_set_test() {
+            let set = $e::A | $e::B | $e::D | $e::E;
+            assert_eq!(set.iter().collect_set(), set);
+            let mut iter = set.iter();
+            iter.next();
+            assert_eq!(iter.clone().collect_set(), $e::B | $e::D | $e::E);
+            iter.next_back();
+            assert_eq!(iter.clone().collect_set(), $e::B | $e::D);
+            iter.next();
+            iter.next();
+            assert_eq!(iter.collect_set(), EnumSet::empty());
+        }
+
+        #[test]
+        fn zip_values_test() {
+            let values: Vec<u32> = (0..128).map(|x| x * 10).collect();
+            let set = $e::A | $e::C | $e::E;
+            let pairs: Vec<_> = set.zip_values(&values).collect();
+            assert_eq!(pairs.len(), 3);
+            for (&(v, &value), expected) in pairs.iter().zip(set.iter()) {
+                assert_eq!(v, expected);
+                assert_eq!(value, v as u32 * 10);
+            }
+
+            let short = &values[..$e::C as usize];
+            let pairs: Vec<_> = set.zip_values(short).map(|(v, _)| v).collect();
+            assert_eq!(pairs, set.iter().filter(|&v| (v as u32) < $e::C as u32).collect::<Vec<_>>());
+            assert_eq!(set.zip_values::<u32>(&[]).next(), None);
+        }
+
+        #[test]
+        fn iter_indices_test() {
+            let set = $e::A | $e::C | $e::E;
+            let indices: Vec<u32> = set.iter_indices().collect();
+            assert_eq!(indices, set.iter().map(|v| v as u32).collect::<Vec<_>>());
+            assert_eq!(set.iter_indices().len(), 3);
+            let all = EnumSet::<$e>::all();
+            assert!(all.iter_indices().eq(all.iter().map(|v| v as u32)));
+            assert_eq!(EnumSet::<$e>::empty().iter_indices().next(), None);
+        }
+
+        #[test]
+        fn debug_positions_test() {
+            let set = $e::A | $e::C | $e::E;
+            let positions: Vec<(u32, $e)> = set.debug_positions().collect();
+            assert_eq!(positions.len(), 3);
+            for (&(position, variant), expected) in positions.iter().zip(set.iter()) {
+                assert_eq!(variant, expected);
+                assert_eq!(position, variant as u32);
+            }
+            assert_eq!(set.debug_positions().next_back(), Some(($e::E as u32, $e::E)));
+        }
+
+        #[test]
+        fn toggle_each_test() {
+            let mut set = $e::A | $e::B;
+            set.toggle_each(vec![$e::B, $e::C]);
+            assert_eq!(set, $e::A | $e::C);
+            set.toggle_each(vec![$e::D, $e::D, $e::E, $e::E, $e::E]);
+            assert_eq!(set, $e::A | $e::C | $e::E);
+            set.toggle_each(EnumSet::<$e>::empty());
+            assert_eq!(set, $e::A | $e::C | $e::E);
+        }
+
+        #[test]
+        fn max_min_by_key_test() {
+            let set = $e::A | $e::B | $e::C | $e::D;
+            let weight = |v: $e| match v {
+                $e::B => 3,
+                $e::C => 3,
+                $e::D => 1,
+                _ => 2,
+            };
+            assert_eq!(set.max_by_key(weight), Some($e::B));
+            assert_eq!(set.min_by_key(weight), Some($e::D));
+            assert_eq!(set.max_by_key(|_| 0), Some($e::A));
+            assert_eq!(set.min_by_key(|_| 0), Some($e::A));
+            assert_eq!(EnumSet::<$e>::empty().max_by_key(weight), None);
+            assert_eq!(EnumSet::<$e>::empty().min_by_key(weight), None);
+        }
+
+        #[test]
+        fn find_map_variant_test() {
+            let set = $e::B | $e::D | $e::E;
+            let mut visited = Vec::new();
+            let found = set.find_map_variant(|v| {
+                visited.push(v);
+                if v == $e::D { Some("found") } else { None }
+            });
+            assert_eq!(found, Some("found"));
+            assert_eq!(visited, &[$e::B, $e::D]);
+            assert_eq!(set.find_map_variant(|v| if v == $e::A { Some(()) } else { None }), None);
+        }
+
+        #[test]
+        fn subsets_test() {
+            for set in [
+                EnumSet::<$e>::empty(),
+                EnumSet::only($e::C),
+                $e::A | $e::C,
+                $e::A | $e::C | $e::D | $e::F | $e::G,
+            ] {
+                let mut iter = set.subsets();
+                assert_eq!(iter.len(), 1 << set.len());
+                assert_eq!(set.subsets().count(), 1 << set.len());
+
+                let mut seen = HashSet::new();
+                while let Some(subset) = iter.next() {
+                    assert!(subset.is_subset(set));
+                    assert!(seen.insert(subset));
+                    assert_eq!(iter.len(), (1 << set.len()) - seen.len());
+                }
+                assert_eq!(iter.len(), 0);
+                assert_eq!(iter.next(), None);
+                assert_eq!(iter.next(), None);
+            }
+        }
+
+        #[test]
+        fn subsets_rev_test() {
+            for set in [
+                EnumSet::<$e>::empty(),
+                EnumSet::only($e::C),
+                $e::A | $e::C | $e::D | $e::F | $e::G,
+            ] {
+                let forward: Vec<_> = set.subsets().collect();
+                let mut backward: Vec<_> = set.subsets().rev().collect();
+                assert_eq!(backward.first(), Some(&set));
+                backward.reverse();
+                assert_eq!(forward, backward);
+            }
+        }
+
+        #[test]
+        fn subsets_both_ends_test() {
+            let set = $e::A | $e::C | $e::D | $e::F;
+            let all: Vec<_> = set.subsets().collect();
+            for split in 0..=all.len() {
+                let mut iter = set.subsets();
+                let mut front = Vec::new();
+                let mut back = Vec::new();
+                for _ in 0..split {
+                    front.push(iter.next().unwrap());
+                }
+                assert_eq!(iter.len(), all.len() - split);
+                while let Some(subset) = iter.next_back() {
+                    back.push(subset);
+                }
+                assert_eq!(iter.next(), None);
+                back.reverse();
+                front.extend(back);
+                assert_eq!(front, all);
+            }
+        }
+
+        #[test]
+        fn subsets_of_size_test() {
+            let set = $e::A | $e::C | $e::D | $e::F | $e::G;
+            let counts = [1, 5, 10, 10, 5, 1, 0];
+            for (k, &count) in counts.iter().enumerate() {
+                let mut iter = set.subsets_of_size(k);
+                for left in (0..=count).rev() {
+                    assert_eq!(iter.size_hint(), (left, Some(left)));
+                    assert_eq!(iter.next().is_some(), left != 0);
+                }
+
+                let subsets: Vec<EnumSet<$e>> = set.subsets_of_size(k).collect();
+                assert_eq!(subsets.len(), count);
+                for subset in &subsets {
+                    assert_eq!(subset.len(), k);
+                    assert!(subset.is_subset(set));
+                }
+                let distinct: HashSet<EnumSet<$e>> = subsets.iter().cloned().collect();
+                assert_eq!(distinct.len(), count);
+            }
+            assert_eq!(set.subsets_of_size(0).collect::<Vec<_>>(), vec![EnumSet::empty()]);
+            assert_eq!(set.subsets_of_size(5).collect::<Vec<_>>(), vec![set]);
+            assert_eq!(EnumSet::<$e>::empty().subsets_of_size(1).next(), None);
+        }
+
+        #[test]
+        fn subsets_with_complement_test() {
+            let set = $e::A | $e::C | $e::D | $e::F;
+            let mut iter = set.subsets_with_complement();
+            assert_eq!(iter.len(), 16);
+            iter.next();
+            assert_eq!(iter.size_hint(), (15, Some(15)));
+
+            let pairs: Vec<_> = set.subsets_with_complement().collect();
+            assert_eq!(pairs.len(), 16);
+            for &(subset, complement) in &pairs {
+                assert!(subset.is_disjoint(complement));
+                assert_eq!(subset | complement, set);
+            }
+            assert_eq!(pairs[0], (EnumSet::empty(), set));
+            assert_eq!(
+                EnumSet::<$e>::empty().subsets_with_complement().collect::<Vec<_>>(),
+                vec![(EnumSet::empty(), EnumSet::empty())],
+            );
+        }
+
+        #[test]
+        fn fold_subsets_test() {
+            let set = $e::A | $e::C | $e::D | $e::F;
+            assert_eq!(set.fold_subsets(0, |acc, _| acc + 1), 16);
+            assert_eq!(set.fold_subsets(EnumSet::empty(), |acc, subset| {
+                assert!(subset.is_subset(set));
+                acc | subset
+            }), set);
+            assert_eq!(EnumSet::<$e>::empty().fold_subsets(0, |acc, _| acc + 1), 1);
+        }
+
+        #[test]
+        fn saturate_test() {
+            // A implies B, B implies C, C implies A, and D implies E.
+            let calls = std::cell::Cell::new(0);
+            let implies = |v| {
+                calls.set(calls.get() + 1);
+                match v {
+                    $e::A => EnumSet::only($e::B),
+                    $e::B => EnumSet::only($e::C),
+                    $e::C => EnumSet::only($e::A),
+                    $e::D => EnumSet::only($e::E),
+                    _ => EnumSet::empty(),
+                }
+            };
+            assert_eq!(EnumSet::only($e::A).saturate(implies), $e::A | $e::B | $e::C);
+            assert_eq!(calls.get(), 3);
+            assert_eq!(EnumSet::only($e::D).saturate(implies), $e::D | $e::E);
+            assert_eq!(($e::C | $e::F).saturate(implies), $e::A | $e::B | $e::C | $e::F);
+            assert_eq!(EnumSet::<$e>::empty().saturate(implies), EnumSet::empty());
+            assert_eq!(EnumSet::<$e>::all().saturate(implies), EnumSet::all());
+        }
+
+        #[test]
+        fn minimize_test() {
+            // A implies B, B implies C, C implies A, and D implies E.
+            let implies = |v| match v {
+                $e::A => EnumSet::only($e::B),
+                $e::B => EnumSet::only($e::C),
+                $e::C => EnumSet::only($e::A),
+                $e::D => EnumSet::only($e::E),
+                _ => EnumSet::empty(),
+            };
+            let set = $e::A | $e::B | $e::C | $e::D | $e::E | $e::F;
+            let minimal = set.minimize(implies);
+            assert_eq!(minimal, $e::A | $e::D | $e::F);
+            assert_eq!(minimal.saturate(implies), set.saturate(implies));
+            assert_eq!(($e::C | $e::E).minimize(implies), $e::C | $e::E);
+            assert_eq!(EnumSet::<$e>::empty().minimize(implies), EnumSet::empty());
+        }
+
+        #[test]
+        fn most_common_test() {
+            let sets = [$e::A | $e::C, $e::C | $e::D, $e::B | $e::D, EnumSet::empty()];
+            assert_eq!(most_common(&sets), Some($e::C));
+            assert_eq!(most_common(&sets[1..]), Some($e::D));
+            assert_eq!(most_common(&[$e::E | $e::B]), Some($e::B));
+            assert_eq!(most_common::<$e>(&[EnumSet::empty()]), None);
+            assert_eq!(most_common::<$e>(&[]), None);
+        }
+
+        #[test]
+        fn filter_test() {
+            let set = $e::A | $e::B | $e::D | $e::E;
+            let pred = |v: $e| v == $e::B || v == $e::E || v == $e::F;
+            let mut calls = 0;
+            let matching = set.filter(|v| {
+                calls += 1;
+                pred(v)
+            });
+            assert_eq!(matching, $e::B | $e::E);
+            assert_eq!(calls, 4);
+            let rest = set.filter(|v| !pred(v));
+            assert_eq!(rest, $e::A | $e::D);
+            assert_eq!(matching | rest, set);
+            assert!(matching.is_disjoint(rest));
+            assert_eq!(set.filter(|_| false), EnumSet::empty());
+            assert_eq!(EnumSet::<$e>::all().filter(|_| true), EnumSet::all());
+        }
+
+        #[test]
+        fn count_matching_test() {
+            let set = $e::A | $e::B | $e::C | $e::E;
+            let odd = |v: $e| v as u32 % 2 == 1;
+            assert_eq!(set.count_matching(odd), set.iter().filter(|&v| odd(v)).count());
+            assert_eq!(set.count_matching(odd), set.filter(odd).len());
+            assert_eq!(set.count_matching(|_| true), 4);
+            assert_eq!(set.count_matching(|_| false), 0);
+            assert_eq!(EnumSet::<$e>::empty().count_matching(|_| true), 0);
+        }
+
+        #[test]
+        fn partition_by_test() {
+            let set = $e::A | $e::B | $e::D | $e::E;
+            let mut calls = 0;
+            let (matching, rest) = set.partition_by(|v| {
+                calls += 1;
+                v == $e::B || v == $e::E || v == $e::F
+            });
+            assert_eq!(matching, $e::B | $e::E);
+            assert_eq!(rest, $e::A | $e::D);
+            assert_eq!(calls, 4);
+            assert_eq!(set.partition_by(|_| true), (set, EnumSet::empty()));
+        }
+
+        #[test]
+        fn partition_point_test() {
+            let set = $e::A | $e::C | $e::D | $e::F;
+            let elements: Vec<$e> = set.iter().collect();
+            for (i, &v) in elements.iter().enumerate() {
+                let mut calls = 0;
+                let result = set.partition_point(|x| {
+                    calls += 1;
+                    (x as u32) < (v as u32)
+                });
+                assert_eq!(result, Some(elements[i]));
+                assert!(calls <= 3);
+            }
+            assert_eq!(set.partition_point(|_| true), None);
+            assert_eq!(set.partition_point(|_| false), set.iter().next());
+            assert_eq!(EnumSet::<$e>::empty().partition_point(|_| false), None);
+        }
+
+        #[test]
+        fn position_of_test() {
+            let set = $e::A | $e::C | $e::D;
+            assert_eq!(set.position_of($e::A), Some(0));
+            assert_eq!(set.position_of($e::C), Some(1));
+            assert_eq!(set.position_of($e::D), Some(2));
+            assert_eq!(set.position_of($e::B), None);
+            for (i, v) in EnumSet::<$e>::all().iter().enumerate() {
+                assert_eq!(EnumSet::<$e>::all().position_of(v), Some(i));
+            }
+        }
+
         #[test]
         fn iter_ordering_test() {
             let set_a = $e::A | $e::B | $e::E;
@@ -273,6 +905,89 @@ macro_rules! test_enum {
             assert!(!($e::A | $e::B | $e::C | $e::D).is_disjoint($e::D | $e::E | $e::F));
             assert!(($e::A | $e::B).is_subset($e::A | $e::B | $e::C));
             assert!(!($e::A | $e::D).is_subset($e::A | $e::B | $e::C));
+            assert!(($e::A | $e::B).is_proper_subset($e::A | $e::B | $e::C));
+            assert!(!($e::A | $e::B).is_proper_subset($e::A | $e::B));
+            assert!(!($e::A | $e::D).is_proper_subset($e::A | $e::B | $e::C));
+            assert!(($e::A | $e::B | $e::C).is_proper_superset($e::A | $e::B));
+            assert!(!($e::A | $e::B).is_proper_superset($e::A | $e::B));
+            assert!(!($e::A | $e::B).is_proper_superset($e::A | $e::D));
+            assert!(!EnumSet::<$e>::empty().is_proper_subset(EnumSet::empty()));
+            assert!(!EnumSet::<$e>::empty().is_proper_superset(EnumSet::empty()));
+            assert!(($e::A | $e::D).has_extra_beyond($e::A | $e::B | $e::C));
+            assert!(!($e::A | $e::B).has_extra_beyond($e::A | $e::B | $e::C));
+            assert!(!EnumSet::<$e>::empty().has_extra_beyond(EnumSet::empty()));
+            assert_eq!(($e::A | $e::B).require_subset_of($e::A | $e::B | $e::C), Ok(()));
+            assert_eq!(($e::A | $e::D | $e::E).require_subset_of($e::A | $e::B), Err($e::D | $e::E));
+        }
+
+        #[test]
+        fn combined_counts_test() {
+            let a = $e::A | $e::B | $e::C;
+            let b = $e::C | $e::D;
+            assert_eq!(a.union_len(b), 4);
+            assert_eq!(a.intersection_count(b), 1);
+            assert_eq!(a.difference_count(b), 2);
+            assert_eq!(b.difference_count(a), 1);
+            assert_eq!(a.symmetric_difference_count(b), 3);
+            assert_eq!(a.union_len(EnumSet::empty()), a.len());
+            assert_eq!(a.intersection_count(EnumSet::empty()), 0);
+        }
+
+        #[test]
+        fn apply_op_test() {
+            let a = $e::A | $e::B;
+            let b = $e::B | $e::C;
+            assert_eq!(a.apply_op(SetOp::Union, b), $e::A | $e::B | $e::C);
+            assert_eq!(a.apply_op(SetOp::Intersection, b), $e::B);
+            assert_eq!(a.apply_op(SetOp::Difference, b), $e::A);
+            assert_eq!(a.apply_op(SetOp::SymmetricDifference, b), $e::A | $e::C);
+        }
+
+        #[test]
+        fn zip_with_test() {
+            let a = $e::A | $e::B;
+            let b = $e::B | $e::C;
+            assert_eq!(a.zip_with(b, |x, y| x || y), a | b);
+            assert_eq!(a.zip_with(b, |x, y| x && y), a & b);
+            assert_eq!(a.zip_with(b, |x, y| x != y), a ^ b);
+            assert_eq!(a.zip_with(b, |x, y| !x && !y), !(a | b));
+            assert_eq!(a.zip_with(b, |_, _| true), EnumSet::all());
+        }
+
+        #[test]
+        fn every_nth_position_test() {
+            let all = EnumSet::<$e>::all();
+            for step in 1..10 {
+                let expected: EnumSet<$e> = all.iter().filter(|&v| v as u32 % step == 0).collect();
+                assert_eq!(all.every_nth_position(step), expected);
+            }
+            assert_eq!(all.every_nth_position(1), all);
+            assert!(all.every_nth_position(u32::MAX).len() <= 1);
+        }
+
+        #[test]
+        fn override_with_test() {
+            let base = $e::A | $e::B | $e::E;
+            let higher = $e::C | $e::D;
+            let managed = $e::B | $e::C | $e::F;
+            assert_eq!(base.override_with(higher, managed), $e::A | $e::C | $e::E);
+            assert_eq!(base.override_with(higher, EnumSet::empty()), base);
+            assert_eq!(base.override_with(higher, EnumSet::all()), higher);
+        }
+
+        #[test]
+        fn diff_test() {
+            let from = $e::A | $e::B | $e::C;
+            let to = $e::B | $e::D | $e::E;
+            let diff = from.diff(to);
+            assert_eq!(diff.to_add, $e::D | $e::E);
+            assert_eq!(diff.to_remove, $e::A | $e::C);
+            assert!(!diff.is_empty());
+
+            let mut set = from;
+            diff.apply(&mut set);
+            assert_eq!(set, to);
+            assert!(to.diff(to).is_empty());
         }
 
         #[test]
@@ -284,6 +999,9 @@ macro_rules! test_enum {
         fn to_from_bits() {
             let value = $e::A | $e::C | $e::D | $e::F | $e::E | $e::G;
             assert_eq!(EnumSet::from_u128(value.as_u128()), value);
+            assert_eq!(value.to_u128(), value.as_u128());
+            assert_eq!(EnumSet::<$e>::from_u128(value.to_u128()), value);
+            assert_eq!(EnumSet::<$e>::try_from_u128(value.to_u128()), Some(value));
         }
 
         #[test]
@@ -386,11 +1104,337 @@ tests!(repr_enum_u64, test_enum!(ReprEnum2, 4));
 tests!(repr_enum_isize, test_enum!(ReprEnum3, 4));
 tests!(repr_enum_c, test_enum!(ReprEnum4, 4));
 
+#[test]
+fn reverse_bits() {
+    assert_eq!((Enum8::A | Enum8::B | Enum8::E).reverse_bits(), Enum8::H | Enum8::G | Enum8::D);
+    assert_eq!(EnumSet::<Enum8>::all().reverse_bits(), EnumSet::all());
+    assert_eq!(EnumSet::<Enum8>::empty().reverse_bits(), EnumSet::empty());
+    assert_eq!((SmallEnum::A | SmallEnum::C).reverse_bits(), SmallEnum::Z | SmallEnum::X);
+    assert_eq!(EnumSet::only(Enum128::A).reverse_bits(), Enum128::_127);
+    assert_eq!(EnumSet::only(Enum1::A).reverse_bits(), Enum1::A);
+
+    // Only positions `10` and `70` mirror onto each other in this enum.
+    assert_eq!((SparseEnum::A | SparseEnum::B).reverse_bits(), SparseEnum::G | SparseEnum::F);
+    assert_eq!((SparseEnum::A | SparseEnum::H).reverse_bits(), SparseEnum::G);
+}
+
+#[test]
+fn rotate() {
+    let set = Enum8::A | Enum8::B | Enum8::G;
+    assert_eq!(set.rotate_left(1), Enum8::B | Enum8::C | Enum8::H);
+    assert_eq!(set.rotate_left(2), Enum8::C | Enum8::D | Enum8::A);
+    assert_eq!(set.rotate_right(1), Enum8::A | Enum8::F | Enum8::H);
+    assert_eq!(set.rotate_left(8), set);
+    assert_eq!(set.rotate_left(0), set);
+    assert_eq!(set.rotate_right(8), set);
+    assert_eq!(set.rotate_left(3).rotate_right(3), set);
+    assert_eq!(set.rotate_left(u32::MAX), set.rotate_left(u32::MAX % 8));
+    assert_eq!(EnumSet::<Enum8>::all().rotate_left(5), EnumSet::all());
+    assert_eq!(EnumSet::<Enum128>::all().rotate_right(77), EnumSet::all());
+    assert_eq!(EnumSet::only(Enum128::_127).rotate_left(1), Enum128::A);
+    assert_eq!(EnumSet::only(Enum1::A).rotate_left(3), Enum1::A);
+
+    // Position `80` wraps to position `0`, which is not a variant of this enum.
+    assert_eq!((SparseEnum::A | SparseEnum::H).rotate_left(10), SparseEnum::B);
+    assert_eq!(EnumSet::only(SparseEnum::A).rotate_right(10), EnumSet::empty());
+    assert_eq!(EnumSet::only(SparseEnum::H).rotate_left(81), SparseEnum::H);
+}
+
+#[test]
+fn subsets_size_hint_large() {
+    let all = EnumSet::<Enum128>::all();
+    assert_eq!(all.subsets().size_hint(), (usize::MAX, Some(usize::MAX)));
+    assert_eq!(all.subsets().len(), usize::MAX);
+    let mut iter = (all - Enum128::A).subsets();
+    assert_eq!(iter.size_hint(), (usize::MAX, Some(usize::MAX)));
+    assert_eq!(iter.next(), Some(EnumSet::empty()));
+    assert_eq!(iter.len(), usize::MAX);
+
+    let small = EnumSet::<Enum128>::all() & EnumSet::from_u128((1 << 20) - 1);
+    assert_eq!(small.subsets().len(), 1 << 20);
+}
+
+#[test]
+fn subsets_of_size_full_width() {
+    let all = EnumSet::<Enum128>::all();
+    assert_eq!(all.subsets_of_size(128).collect::<Vec<_>>(), vec![all]);
+    assert_eq!(all.subsets_of_size(129).next(), None);
+    assert_eq!(all.subsets_of_size(1).count(), 128);
+    assert_eq!(all.subsets_of_size(127).count(), 128);
+    assert_eq!(all.subsets_of_size(2).count(), 128 * 127 / 2);
+    assert_eq!(all.subsets_of_size(2).len(), 128 * 127 / 2);
+    assert_eq!(all.subsets_of_size(127).len(), 128);
+    assert_eq!(all.subsets_of_size(129).len(), 0);
+    if usize::BITS <= 64 {
+        assert_eq!(all.subsets_of_size(64).size_hint(), (usize::MAX, Some(usize::MAX)));
+    }
+    let mid = all & EnumSet::from_u128((1 << 60) - 1);
+    assert_eq!(mid.subsets_of_size(30).len(), 118264581564861424);
+    assert_eq!(all.subsets_of_size(1).last(), Some(EnumSet::only(Enum128::_127)));
+}
+
+#[test]
+fn is_contiguous() {
+    assert!(EnumSet::<Enum8>::empty().is_contiguous());
+    assert!(EnumSet::<Enum8>::all().is_contiguous());
+    assert!(EnumSet::<Enum128>::all().is_contiguous());
+    assert!(EnumSet::only(Enum8::E).is_contiguous());
+    assert!((Enum8::C | Enum8::D | Enum8::E).is_contiguous());
+    assert!(!(Enum8::C | Enum8::E).is_contiguous());
+    assert!(!(Enum8::A | Enum8::B | Enum8::H).is_contiguous());
+    assert!(!(SparseEnum::A | SparseEnum::B).is_contiguous());
+}
+
+#[test]
+fn starts_at_zero() {
+    assert!((Enum8::A | Enum8::C).starts_at_zero());
+    assert!(!(Enum8::B | Enum8::C).starts_at_zero());
+    assert!(!EnumSet::<Enum8>::empty().starts_at_zero());
+    assert!(!EnumSet::<SparseEnum>::all().starts_at_zero());
+}
+
+#[test]
+fn runs() {
+    use SmallEnum::*;
+    let runs: Vec<_> = (A | B | C | E).runs().collect();
+    assert_eq!(runs, &[(A, C), (E, E)]);
+    let runs: Vec<_> = (B | D | F | G | Y | Z).runs().collect();
+    assert_eq!(runs, &[(B, B), (D, D), (F, G), (Y, Z)]);
+    let runs: Vec<_> = EnumSet::<SmallEnum>::all().runs().collect();
+    assert_eq!(runs, &[(A, Z)]);
+    assert_eq!(EnumSet::<SmallEnum>::empty().runs().count(), 0);
+
+    let runs: Vec<_> = EnumSet::<Enum128>::all().runs().collect();
+    assert_eq!(runs, &[(Enum128::A, Enum128::_127)]);
+    let runs: Vec<_> = (Enum128::_126 | Enum128::_127).runs().collect();
+    assert_eq!(runs, &[(Enum128::_126, Enum128::_127)]);
+
+    let runs: Vec<_> = (SparseEnum::A | SparseEnum::B).runs().collect();
+    assert_eq!(runs, &[(SparseEnum::A, SparseEnum::A), (SparseEnum::B, SparseEnum::B)]);
+}
+
+#[test]
+fn apply_permutation() {
+    use Enum8::*;
+    let swap_pairs = [1, 0, 3, 2, 5, 4, 7, 6];
+    assert_eq!((A | D | G).apply_permutation(&swap_pairs), Some(B | C | H));
+    assert_eq!(EnumSet::<Enum8>::all().apply_permutation(&swap_pairs), Some(EnumSet::all()));
+
+    // Entries for elements not in the set are ignored.
+    assert_eq!((A | B).apply_permutation(&[7, 6]), Some(H | G));
+    assert_eq!((A | B | C).apply_permutation(&[7, 6]), None);
+
+    // Colliding or invalid targets.
+    assert_eq!((A | B).apply_permutation(&[3, 3]), None);
+    assert_eq!(EnumSet::only(A).apply_permutation(&[8]), None);
+    assert_eq!(EnumSet::only(A).apply_permutation(&[200]), None);
+    assert_eq!(EnumSet::only(SparseEnum::A).apply_permutation(&[0; 11]), None);
+}
+
+#[derive(EnumSetType, Debug)]
+pub enum DisplayEnum {
+    Read, Write, Exec,
+}
+impl std::fmt::Display for DisplayEnum {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            DisplayEnum::Read => "read",
+            DisplayEnum::Write => "write",
+            DisplayEnum::Exec => "exec",
+        })
+    }
+}
+
+impl std::str::FromStr for DisplayEnum {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "read" => Ok(DisplayEnum::Read),
+            "write" => Ok(DisplayEnum::Write),
+            "exec" => Ok(DisplayEnum::Exec),
+            _ => Err(s.to_string()),
+        }
+    }
+}
+
+#[test]
+fn iter_is_fused() {
+    fn drain_fused<I: std::iter::FusedIterator>(mut iter: I) -> usize {
+        let count = iter.by_ref().count();
+        assert!(iter.next().is_none());
+        count
+    }
+    let set = SmallEnum::A | SmallEnum::C | SmallEnum::Z;
+    assert_eq!(drain_fused(set.iter()), 3);
+    assert_eq!(drain_fused(set.iter().rev()), 3);
+}
+
+#[test]
+fn map() {
+    let set = SparseEnum::A | SparseEnum::B | SparseEnum::E;
+    let mapped = set.map(|v| match v {
+        SparseEnum::A | SparseEnum::B => Enum8::A,
+        _ => Enum8::H,
+    });
+    assert_eq!(mapped, Enum8::A | Enum8::H);
+    assert_eq!(mapped.len(), 2);
+    assert_eq!(set.map(|_| Enum8::C), Enum8::C);
+    assert_eq!(EnumSet::<SparseEnum>::empty().map(|_| Enum8::C), EnumSet::empty());
+    assert_eq!(EnumSet::<SmallEnum>::all().map(|v| v), EnumSet::all());
+}
+
+#[test]
+fn try_map() {
+    let set = SparseEnum::A | SparseEnum::B | SparseEnum::E;
+    let to_enum8 = |v| match v {
+        SparseEnum::A => Ok(Enum8::A),
+        SparseEnum::B => Ok(Enum8::B),
+        SparseEnum::E => Ok(Enum8::B),
+        other => Err(other),
+    };
+    assert_eq!(set.try_map(to_enum8), Ok(Enum8::A | Enum8::B));
+    assert_eq!(EnumSet::<SparseEnum>::empty().try_map(to_enum8), Ok(EnumSet::empty()));
+
+    let mut visited = Vec::new();
+    let result = (set | SparseEnum::C | SparseEnum::D).try_map(|v| {
+        visited.push(v);
+        to_enum8(v)
+    });
+    assert_eq!(result, Err(SparseEnum::C));
+    assert_eq!(visited, &[SparseEnum::A, SparseEnum::B, SparseEnum::C]);
+}
+
+#[test]
+fn count_matching_odd() {
+    let odd = |v: SmallEnum| v as u32 % 2 == 1;
+    assert_eq!(EnumSet::<SmallEnum>::all().count_matching(odd), 13);
+    assert_eq!((SmallEnum::A | SmallEnum::B | SmallEnum::D).count_matching(odd), 2);
+    assert_eq!(EnumSet::<SparseEnum>::all().count_matching(|v| v as u32 % 2 == 1), 0);
+}
+
+#[test]
+fn const_queries() {
+    const SET: EnumSet<SparseEnum> = enum_set!(SparseEnum::A | SparseEnum::C | SparseEnum::H);
+    const EMPTY: EnumSet<Enum128> = enum_set!();
+    const LARGE: EnumSet<Enum128> = enum_set!(Enum128::A | Enum128::_127);
+
+    const _: () = assert!(SET.contains(SparseEnum::A));
+    const _: () = assert!(SET.contains(SparseEnum::H));
+    const _: () = assert!(!SET.contains(SparseEnum::B));
+    const _: () = assert!(!SET.is_empty());
+    const _: () = assert!(SET.len() == 3);
+    const _: () = assert!(EMPTY.is_empty() && !LARGE.is_empty());
+    const _: () = assert!(LARGE.contains(Enum128::_127));
+    const _: () = assert!(!LARGE.contains(Enum128::_126));
+    const _: () = assert!(LARGE.len() == 2);
+
+    assert!(SET.contains(SparseEnum::C));
+    assert_eq!(LARGE.len(), 2);
+    assert!(EMPTY.is_empty());
+}
+
+#[test]
+fn first_last_sparse() {
+    let set = SparseEnum::B | SparseEnum::D | SparseEnum::H;
+    assert_eq!(set.first(), Some(SparseEnum::B));
+    assert_eq!(set.first().map(|x| x as u32), Some(20));
+    assert_eq!(set.last(), Some(SparseEnum::H));
+    assert_eq!(set.last().map(|x| x as u32), Some(80));
+    assert_eq!(EnumSet::<SparseEnum>::all().first(), Some(SparseEnum::A));
+}
+
+#[test]
+fn from_str() {
+    use DisplayEnum::*;
+    assert_eq!("read | exec".parse(), Ok(Read | Exec));
+    assert_eq!("  write|read ".parse(), Ok(Read | Write));
+    assert_eq!("exec".parse(), Ok(EnumSet::only(Exec)));
+    assert_eq!("".parse(), Ok(EnumSet::<DisplayEnum>::empty()));
+    assert_eq!("   ".parse(), Ok(EnumSet::<DisplayEnum>::empty()));
+    assert_eq!("read | | exec".parse::<EnumSet<DisplayEnum>>(), Err(EnumSetParseError::EmptyToken));
+    assert_eq!("read |".parse::<EnumSet<DisplayEnum>>(), Err(EnumSetParseError::EmptyToken));
+    assert_eq!(
+        "read | list".parse::<EnumSet<DisplayEnum>>(),
+        Err(EnumSetParseError::Element("list".to_string())),
+    );
+
+    let set = EnumSet::<DisplayEnum>::all();
+    assert_eq!(set.to_string().parse(), Ok(set));
+}
+
+#[test]
+fn display() {
+    use DisplayEnum::*;
+    assert_eq!((Read | Exec).to_string(), "read | exec");
+    assert_eq!(EnumSet::only(Write).to_string(), "write");
+    assert_eq!(EnumSet::<DisplayEnum>::empty().to_string(), "");
+    assert_eq!(EnumSet::<DisplayEnum>::all().display_with(", ").to_string(), "read, write, exec");
+    assert_eq!(EnumSet::only(Exec).display_with(", ").to_string(), "exec");
+    assert_eq!(EnumSet::<DisplayEnum>::empty().display_with(", ").to_string(), "");
+}
+
+#[test]
+fn debug_alternate() {
+    use SmallEnum::*;
+    assert_eq!(format!("{:#?}", A | B | C | E), "EnumSet(A..C | E)");
+    assert_eq!(format!("{:#?}", EnumSet::<SmallEnum>::all()), "EnumSet(A..Z)");
+    assert_eq!(format!("{:#?}", B | D | E), "EnumSet(B | D..E)");
+    assert_eq!(format!("{:#?}", EnumSet::<SmallEnum>::empty()), "EnumSet()");
+    assert_eq!(format!("{:?}", A | B | C), "EnumSet(A | B | C)");
+    assert_eq!(format!("{:#?}", SparseEnum::A | SparseEnum::B), "EnumSet(A | B)");
+}
+
+#[test]
+fn single() {
+    const SINGLE_1: EnumSet<Enum1> = EnumSet::single(Enum1::A);
+    assert_eq!(SINGLE_1, EnumSet::<Enum1>::all());
+    for v in EnumSet::<Enum128>::all() {
+        assert_eq!(EnumSet::single(v), EnumSet::only(v));
+    }
+    for v in EnumSet::<SparseEnum>::all() {
+        assert_eq!(EnumSet::single(v), EnumSet::only(v));
+    }
+}
+
+#[derive(EnumSetType, Debug)]
+#[enumset(subset_of = "SmallEnum")]
+pub enum SmallSubsetEnum {
+    B = 1, D = 3, Z = 25,
+}
+#[derive(EnumSetType, Debug)]
+#[enumset(subset_of = "crate::SparseEnum")]
+pub enum SparseSubsetEnum {
+    A = 0xA, H = 80,
+}
+
+#[test]
+fn narrow() {
+    let set = SmallEnum::A | SmallEnum::B | SmallEnum::Z;
+    assert_eq!(set.narrow::<SmallSubsetEnum>(), SmallSubsetEnum::B | SmallSubsetEnum::Z);
+    assert_eq!(EnumSet::<SmallEnum>::all().narrow(), EnumSet::<SmallSubsetEnum>::all());
+    assert!(EnumSet::only(SmallEnum::C).narrow::<SmallSubsetEnum>().is_empty());
+
+    let sparse = SparseEnum::B | SparseEnum::H;
+    assert_eq!(sparse.narrow::<SparseSubsetEnum>(), SparseSubsetEnum::H);
+}
+
 #[derive(EnumSetType, Debug)]
 pub enum ThresholdEnum {
     A = 1, B, C, D,
     U8 = 0, U16 = 8, U32 = 16, U64 = 32, U128 = 64,
 }
+#[test]
+fn minimal_repr_width() {
+    use crate::ThresholdEnum::*;
+    assert_eq!(EnumSet::<ThresholdEnum>::empty().minimal_repr_width(), 8);
+    assert_eq!((U8 | A | D).minimal_repr_width(), 8);
+    assert_eq!((U8 | U16).minimal_repr_width(), 16);
+    assert_eq!(EnumSet::only(U32).minimal_repr_width(), 32);
+    assert_eq!((A | U64).minimal_repr_width(), 64);
+    assert_eq!(EnumSet::only(U128).minimal_repr_width(), 128);
+    assert_eq!(EnumSet::<ThresholdEnum>::bit_width(), 65);
+}
+
 macro_rules! bits_tests {
     (
         $mod_name:ident, $threshold_expr:expr, ($($too_big_expr:expr),*), $ty:ty,
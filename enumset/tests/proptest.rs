@@ -0,0 +1,76 @@
+#![cfg(feature = "proptest")]
+
+use enumset::*;
+use proptest::prelude::*;
+use proptest::strategy::ValueTree;
+use proptest::test_runner::TestRunner;
+
+#[derive(EnumSetType, Debug)]
+pub enum Enum {
+    A, B, C, D, E, F, G, H,
+}
+
+#[derive(EnumSetType, Debug)]
+pub enum SparseEnum {
+    A = 10, B = 50, C = 127,
+}
+
+proptest! {
+    #[test]
+    fn double_complement(set in any_enumset::<Enum>()) {
+        prop_assert_eq!(set.complement().complement(), set);
+    }
+
+    #[test]
+    fn generated_sets_are_valid(set in any_enumset::<SparseEnum>()) {
+        prop_assert!(set.is_valid());
+    }
+}
+
+#[test]
+fn generates_empty_and_full() {
+    let mut runner = TestRunner::deterministic();
+    let strategy = any_enumset::<Enum>();
+    let (mut empty, mut full) = (false, false);
+    for _ in 0..200 {
+        let set = strategy.new_tree(&mut runner).unwrap().current();
+        empty |= set.is_empty();
+        full |= set == EnumSet::all();
+    }
+    assert!(empty);
+    assert!(full);
+}
+
+#[test]
+fn shrinks_toward_empty() {
+    let mut runner = TestRunner::deterministic();
+    let strategy = any_enumset::<Enum>();
+    let mut tree = strategy.new_tree(&mut runner).unwrap();
+    while tree.current().len() < 3 {
+        tree = strategy.new_tree(&mut runner).unwrap();
+    }
+
+    let mut previous = tree.current();
+    while tree.simplify() {
+        let current = tree.current();
+        assert!(current.is_subset(previous));
+        assert_eq!(current.len() + 1, previous.len());
+        previous = current;
+    }
+    assert!(tree.current().is_empty());
+}
+
+#[test]
+fn shrinking_finds_minimal_failure() {
+    let mut runner = TestRunner::deterministic();
+    let result = runner.run(&any_enumset::<Enum>(), |set| {
+        prop_assert!(!set.is_superset(Enum::B | Enum::E));
+        Ok(())
+    });
+    match result {
+        Err(proptest::test_runner::TestError::Fail(_, set)) => {
+            assert_eq!(set, Enum::B | Enum::E);
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
@@ -0,0 +1,130 @@
+#![cfg(feature = "rand")]
+
+use enumset::*;
+use rand::rngs::SmallRng;
+use rand::SeedableRng;
+
+#[derive(EnumSetType, Debug)]
+pub enum Enum {
+    A, B, C, D, E, F, G, H,
+}
+
+#[derive(EnumSetType, Debug)]
+pub enum LargeEnum {
+    A = 10, B = 50, C = 127,
+}
+
+#[test]
+fn shuffled_yields_each_element_once() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let set = Enum::A | Enum::C | Enum::D | Enum::H;
+    for _ in 0..100 {
+        let iter = set.shuffled(&mut rng);
+        assert_eq!(iter.len(), set.len());
+        let items: Vec<Enum> = iter.collect();
+        assert_eq!(items.len(), set.len());
+        assert_eq!(items.into_iter().collect::<EnumSet<_>>(), set);
+    }
+
+    let large = EnumSet::<LargeEnum>::all();
+    assert_eq!(large.shuffled(&mut rng).collect::<EnumSet<_>>(), large);
+    assert_eq!(EnumSet::<Enum>::empty().shuffled(&mut rng).next(), None);
+}
+
+#[test]
+fn shuffled_visits_all_orders() {
+    let mut rng = SmallRng::seed_from_u64(1);
+    let set = Enum::B | Enum::E | Enum::F;
+    let mut seen = std::collections::HashSet::new();
+    for _ in 0..1000 {
+        seen.insert(set.shuffled(&mut rng).map(|v| v as u32).collect::<Vec<_>>());
+    }
+    assert_eq!(seen.len(), 6);
+}
+
+#[test]
+fn weighted_choice() {
+    let mut rng = SmallRng::seed_from_u64(2);
+    let set = Enum::A | Enum::B | Enum::C | Enum::D;
+    let weight = |v| match v {
+        Enum::A => 1.0,
+        Enum::B => 3.0,
+        Enum::C => 0.0,
+        _ => -1.0,
+    };
+    let mut counts = [0; 2];
+    for _ in 0..4000 {
+        match set.weighted_choice(&mut rng, weight) {
+            Some(Enum::A) => counts[0] += 1,
+            Some(Enum::B) => counts[1] += 1,
+            other => panic!("unexpected choice {:?}", other),
+        }
+    }
+    assert!(counts[1] > counts[0] * 2 && counts[1] < counts[0] * 4);
+
+    assert_eq!(EnumSet::only(Enum::H).weighted_choice(&mut rng, |_| 5u8), Some(Enum::H));
+    assert_eq!(set.weighted_choice(&mut rng, |_| 0.0), None);
+    assert_eq!(set.weighted_choice(&mut rng, |_| f64::NAN), None);
+    assert_eq!(EnumSet::<Enum>::empty().weighted_choice(&mut rng, |_| 1.0), None);
+}
+
+#[test]
+fn weighted_choice_non_finite() {
+    let mut rng = SmallRng::seed_from_u64(7);
+    let set = Enum::A | Enum::B;
+    assert_eq!(set.weighted_choice(&mut rng, |_| f64::INFINITY), None);
+    for _ in 0..100 {
+        let weight = |v| if v == Enum::A { f64::INFINITY } else { 1.0 };
+        assert_eq!(set.weighted_choice(&mut rng, weight), Some(Enum::B));
+    }
+
+    // The total of these weights overflows an `f64`, but both should still be chosen.
+    let mut counts = [0; 2];
+    for _ in 0..1000 {
+        match set.weighted_choice(&mut rng, |_| f64::MAX) {
+            Some(Enum::A) => counts[0] += 1,
+            Some(Enum::B) => counts[1] += 1,
+            other => panic!("unexpected choice {:?}", other),
+        }
+    }
+    assert!(counts[0] > 400 && counts[1] > 400);
+}
+
+#[test]
+fn sample() {
+    let mut rng = SmallRng::seed_from_u64(3);
+    let set = Enum::B | Enum::D | Enum::G;
+    let mut counts = [0; 8];
+    for _ in 0..3000 {
+        counts[set.sample(&mut rng).unwrap() as usize] += 1;
+    }
+    for &v in &[Enum::B, Enum::D, Enum::G] {
+        assert!(counts[v as usize] > 850 && counts[v as usize] < 1150);
+    }
+    assert_eq!(counts.iter().sum::<i32>(), 3000);
+
+    assert_eq!(EnumSet::only(LargeEnum::C).sample(&mut rng), Some(LargeEnum::C));
+    assert_eq!(EnumSet::<Enum>::empty().sample(&mut rng), None);
+}
+
+#[test]
+fn distribution() {
+    use rand::Rng;
+
+    let mut rng = SmallRng::seed_from_u64(4);
+    let mut counts = [0; 8];
+    for _ in 0..2000 {
+        let set: EnumSet<Enum> = rng.gen();
+        for v in set {
+            counts[v as usize] += 1;
+        }
+    }
+    for &count in &counts {
+        assert!(count > 850 && count < 1150);
+    }
+
+    for _ in 0..100 {
+        let set: EnumSet<LargeEnum> = rng.gen();
+        assert!(set.is_valid());
+    }
+}
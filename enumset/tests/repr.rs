@@ -6,6 +6,12 @@ enum ReprEnum {
     A, B, C, D, E, F, G, H,
 }
 
+#[derive(EnumSetType, Debug)]
+#[enumset(repr = "u16")]
+enum SparseReprEnum {
+    A = 1, B = 3, C = 5,
+}
+
 #[test]
 fn test() {
     let mut set = EnumSet::<ReprEnum>::new();
@@ -21,3 +27,124 @@ fn test() {
     let set2 = unsafe { EnumSet::<ReprEnum>::from_repr_unchecked(repr) };
     assert_eq!(set, set2);
 }
+
+#[test]
+fn modify_repr() {
+    let mut set = ReprEnum::A | ReprEnum::C;
+    let old = set.modify_repr(|repr| {
+        let old = *repr;
+        *repr |= 1 << 7;
+        old
+    });
+    assert_eq!(old, 0b101);
+    assert_eq!(set, ReprEnum::A | ReprEnum::C | ReprEnum::H);
+
+    // Bits that don't correspond to a variant are removed afterwards.
+    set.modify_repr(|repr| *repr = !0);
+    assert_eq!(set, EnumSet::all());
+    assert_eq!(set.as_repr(), 0xFF);
+}
+
+#[test]
+fn repr_layout() {
+    const SIZE: usize = EnumSet::<ReprEnum>::repr_size();
+    const ALIGN: usize = EnumSet::<ReprEnum>::repr_align();
+    assert_eq!(SIZE, std::mem::size_of::<u16>());
+    assert_eq!(ALIGN, std::mem::align_of::<u16>());
+    assert_eq!(SIZE, std::mem::size_of::<EnumSet<ReprEnum>>());
+    assert_eq!(ALIGN, std::mem::align_of::<EnumSet<ReprEnum>>());
+}
+
+#[test]
+fn from_repr_snapshot() {
+    assert_eq!(EnumSet::<ReprEnum>::from_repr_snapshot(0b100010), ReprEnum::B | ReprEnum::F);
+    assert_eq!(EnumSet::<ReprEnum>::from_repr_snapshot(!0), EnumSet::all());
+}
+
+#[test]
+fn transform_repr() {
+    let set = ReprEnum::A | ReprEnum::B | ReprEnum::C;
+    // Clear the lowest set bit.
+    assert_eq!(set.transform_repr(|x| x & (x - 1)), Some(ReprEnum::B | ReprEnum::C));
+    assert_eq!(set.transform_repr(|x| x << 5), Some(ReprEnum::F | ReprEnum::G | ReprEnum::H));
+    assert_eq!(set.transform_repr(|x| x << 6), None);
+}
+
+#[test]
+fn is_valid() {
+    let set = ReprEnum::A | ReprEnum::H;
+    assert!(set.is_valid());
+    set.debug_assert_valid();
+    assert!(EnumSet::<ReprEnum>::all().is_valid());
+
+    let invalid = unsafe { EnumSet::<ReprEnum>::from_repr_unchecked(1 << 8) };
+    assert!(!invalid.is_valid());
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic]
+fn debug_assert_valid() {
+    let invalid = unsafe { EnumSet::<ReprEnum>::from_repr_unchecked(1 << 15) };
+    invalid.debug_assert_valid();
+}
+
+#[test]
+fn from_repr_clamped() {
+    assert_eq!(EnumSet::<ReprEnum>::from_repr_clamped(!0), EnumSet::all());
+    assert_eq!(EnumSet::<ReprEnum>::from_repr_clamped(0b1010), ReprEnum::B | ReprEnum::D);
+    assert_eq!(EnumSet::<ReprEnum>::from_repr_clamped(0), EnumSet::empty());
+
+    // Only the lowest three set bits are kept, and bits 0 and 2 are not valid variants.
+    assert_eq!(EnumSet::<SparseReprEnum>::from_repr_clamped(0b111111), SparseReprEnum::A);
+    assert_eq!(EnumSet::<SparseReprEnum>::from_repr_truncated(0b111111), EnumSet::all());
+    assert_eq!(EnumSet::<SparseReprEnum>::from_repr_clamped(0b101010), EnumSet::all());
+}
+
+#[test]
+fn bit_report() {
+    let report = (ReprEnum::A | ReprEnum::C | ReprEnum::H).bit_report();
+    assert_eq!(report.width, 16);
+    assert_eq!(report.invalid_bits_set, 0);
+    assert_eq!(report.iter_present_positions().collect::<Vec<_>>(), vec![0, 2, 7]);
+    assert_eq!(report.iter_absent_valid_positions().collect::<Vec<_>>(), vec![1, 3, 4, 5, 6]);
+
+    let invalid = unsafe { EnumSet::<ReprEnum>::from_repr_unchecked(0b1000_0001_0000_0010) };
+    let report = invalid.bit_report();
+    assert_eq!(report.invalid_bits_set, 0b1000_0001_0000_0000);
+    assert_eq!(report.iter_present_positions().collect::<Vec<_>>(), vec![1]);
+
+    let report = EnumSet::<SparseReprEnum>::only(SparseReprEnum::B).bit_report();
+    assert_eq!(report.iter_present_positions().collect::<Vec<_>>(), vec![3]);
+    assert_eq!(report.iter_absent_valid_positions().collect::<Vec<_>>(), vec![1, 5]);
+}
+
+#[test]
+fn into_repr() {
+    let set = ReprEnum::B | ReprEnum::F;
+    let repr: u16 = set.into();
+    assert_eq!(repr, set.as_repr());
+    assert_eq!(u16::from(EnumSet::<SparseReprEnum>::all()), 0b101010);
+
+    fn to_bits<S: Into<u16>>(set: S) -> u16 {
+        set.into()
+    }
+    assert_eq!(to_bits(EnumSet::<ReprEnum>::empty()), 0);
+    assert_eq!(to_bits(SparseReprEnum::B | SparseReprEnum::C), 0b101000);
+}
+
+#[test]
+fn try_from_repr_trait() {
+    use std::convert::{TryFrom, TryInto};
+
+    assert_eq!(EnumSet::<ReprEnum>::try_from(0b100010u16), Ok(ReprEnum::B | ReprEnum::F));
+    assert_eq!(EnumSet::<ReprEnum>::try_from(0), Ok(EnumSet::empty()));
+    let set: Result<EnumSet<SparseReprEnum>, _> = 0b101010u16.try_into();
+    assert_eq!(set, Ok(EnumSet::all()));
+
+    let err = EnumSet::<ReprEnum>::try_from(0x0302).unwrap_err();
+    assert_eq!(err, EnumSetReprError { invalid_bits: 0x0300 });
+    assert_eq!(err.to_string(), "bits 0x300 do not correspond to any variant of the enum");
+    let err = EnumSet::<SparseReprEnum>::try_from(0b111).unwrap_err();
+    assert_eq!(err.invalid_bits, 0b101);
+}
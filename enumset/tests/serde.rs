@@ -28,6 +28,67 @@ pub enum DenyUnknownEnum {
     A, B, C, D, E, F, G, H,
 }
 
+#[derive(EnumSetType, Debug)]
+#[enumset(serialize_versioned)]
+pub enum VersionedEnum {
+    A, B, C, D, E, F, G, H,
+}
+
+#[derive(EnumSetType, Debug)]
+#[enumset(serialize_versioned, serialize_repr = "u16", serialize_deny_unknown)]
+pub enum VersionedDenyUnknownEnum {
+    A, B, C, D, E, F, G,
+}
+
+#[derive(EnumSetType, Debug)]
+#[enumset(serialize_repr = "u8")]
+pub enum ReprU8Enum {
+    A, B, C, D, E, F, G, H,
+}
+
+#[derive(EnumSetType, Debug)]
+#[enumset(serialize_repr = "u16")]
+pub enum ReprU16Enum {
+    A, B, C, D, E, F, G, H = 15,
+}
+
+#[derive(EnumSetType, Debug)]
+#[enumset(serialize_repr = "u32")]
+pub enum ReprU32Enum {
+    A, B, C, D, E, F, G, H = 31,
+}
+
+#[derive(EnumSetType, Debug)]
+#[enumset(serialize_repr = "u64")]
+pub enum ReprU64Enum {
+    A, B, C, D, E, F, G, H = 63,
+}
+
+#[derive(EnumSetType, Debug)]
+#[enumset(serialize_repr = "u128")]
+pub enum ReprU128Enum {
+    A, B, C, D, E, F, G, H = 127,
+}
+
+#[derive(EnumSetType, Debug)]
+#[enumset(serialize_as_map)]
+pub enum MapEnum {
+    A, B, C, D, E, F, G, H,
+}
+
+#[derive(EnumSetType, Debug)]
+#[enumset(serialize_as_map, serialize_deny_unknown)]
+pub enum MapDenyUnknownEnum {
+    A, B, C, D, E, F, G, H,
+}
+
+#[derive(EnumSetType, Debug)]
+#[enumset(serialize_as_map)]
+#[allow(non_camel_case_types)]
+pub enum MapSparseEnum {
+    A = 3, r#type = 70, C = 127,
+}
+
 macro_rules! serde_test_simple {
     ($e:ident, $ser_size:expr) => {
         #[test]
@@ -41,6 +102,15 @@ macro_rules! serde_test_simple {
             }
         }
 
+        #[test]
+        fn serialize_deserialize_test_postcard() {
+            let value = $e::A | $e::C | $e::D | $e::F | $e::E | $e::G;
+            let mut buf = [0u8; 64];
+            let serialized = postcard::to_slice(&value, &mut buf).unwrap();
+            let deserialized = postcard::from_bytes::<EnumSet<$e>>(serialized).unwrap();
+            assert_eq!(value, deserialized);
+        }
+
         #[test]
         fn serialize_deserialize_test_json() {
             let value = $e::A | $e::C | $e::D | $e::F | $e::E | $e::G;
@@ -85,6 +155,81 @@ fn test_json_reprs() {
                serde_json::to_string(&(ReprEnum::A | ReprEnum::C | ReprEnum::D)).unwrap());
 }
 
+#[test]
+fn test_versioned() {
+    let value = VersionedEnum::A | VersionedEnum::C | VersionedEnum::D;
+    assert_eq!("[1,13]", serde_json::to_string(&value).unwrap());
+    assert_eq!(value, serde_json::from_str::<EnumSet<VersionedEnum>>("[1,13]").unwrap());
+    assert!(serde_json::from_str::<EnumSet<VersionedEnum>>("[2,13]").is_err());
+    assert!(serde_json::from_str::<EnumSet<VersionedEnum>>("[1]").is_err());
+    assert!(serde_json::from_str::<EnumSet<VersionedEnum>>("13").is_err());
+
+    assert!(serde_json::from_str::<EnumSet<VersionedDenyUnknownEnum>>("[1,255]").is_err());
+    assert_eq!(VersionedDenyUnknownEnum::A | VersionedDenyUnknownEnum::G,
+               serde_json::from_str::<EnumSet<VersionedDenyUnknownEnum>>("[1,65]").unwrap());
+}
+
+#[test]
+fn test_map() {
+    let value = MapEnum::A | MapEnum::C;
+    assert_eq!(
+        r#"{"A":true,"B":false,"C":true,"D":false,"E":false,"F":false,"G":false,"H":false}"#,
+        serde_json::to_string(&value).unwrap(),
+    );
+    assert_eq!(value,
+               serde_json::from_str::<EnumSet<MapEnum>>(r#"{"C":true,"A":true,"B":false}"#).unwrap());
+    assert_eq!(value,
+               serde_json::from_str::<EnumSet<MapEnum>>(r#"{"A":true,"X":[1],"C":true}"#).unwrap());
+    assert!(serde_json::from_str::<EnumSet<MapEnum>>(r#"{"A":1}"#).is_err());
+    assert!(serde_json::from_str::<EnumSet<MapEnum>>(r#"["A"]"#).is_err());
+
+    assert_eq!(MapDenyUnknownEnum::B,
+               serde_json::from_str::<EnumSet<MapDenyUnknownEnum>>(r#"{"B":true}"#).unwrap());
+    assert!(serde_json::from_str::<EnumSet<MapDenyUnknownEnum>>(r#"{"X":true}"#).is_err());
+}
+
+#[test]
+fn test_map_sparse() {
+    let value = MapSparseEnum::r#type | MapSparseEnum::C;
+    assert_eq!(r#"{"A":false,"type":true,"C":true}"#, serde_json::to_string(&value).unwrap());
+    assert_eq!(value,
+               serde_json::from_str::<EnumSet<MapSparseEnum>>(r#"{"type":true,"C":true}"#).unwrap());
+    assert_eq!(EnumSet::<MapSparseEnum>::empty(),
+               serde_json::from_str::<EnumSet<MapSparseEnum>>("{}").unwrap());
+    assert_eq!(EnumSet::<MapEnum>::empty(), serde_json::from_str::<EnumSet<MapEnum>>("{}").unwrap());
+}
+
+#[test]
+fn test_postcard_widths() {
+    fn round_trip<T: EnumSetType + std::fmt::Debug>(set: EnumSet<T>) {
+        let mut buf = [0u8; 32];
+        let serialized = postcard::to_slice(&set, &mut buf).unwrap();
+        assert_eq!(postcard::from_bytes::<EnumSet<T>>(serialized).unwrap(), set);
+    }
+    round_trip(ReprU8Enum::A | ReprU8Enum::H);
+    round_trip(ReprU16Enum::A | ReprU16Enum::H);
+    round_trip(ReprU32Enum::A | ReprU32Enum::H);
+    round_trip(ReprU64Enum::A | ReprU64Enum::H);
+    round_trip(ReprU128Enum::A | ReprU128Enum::H);
+    round_trip(EnumSet::<ReprU128Enum>::all());
+    round_trip(EnumSet::<ReprU8Enum>::empty());
+}
+
+#[test]
+fn test_postcard_deny_unknown() {
+    let mut buf = [0u8; 32];
+    let serialized = postcard::to_slice(&!0u128, &mut buf).unwrap();
+    assert!(postcard::from_bytes::<EnumSet<DenyUnknownEnum>>(serialized).is_err());
+    assert_eq!(postcard::from_bytes::<EnumSet<ReprEnum>>(serialized).unwrap(), EnumSet::all());
+
+    let serialized = postcard::to_slice(&(1u8, 255u16), &mut buf).unwrap();
+    assert!(postcard::from_bytes::<EnumSet<VersionedDenyUnknownEnum>>(serialized).is_err());
+}
+
 tests!(list_enum, serde_test_simple!(ListEnum, !0));
 tests!(repr_enum, serde_test!(ReprEnum, 16));
 tests!(deny_unknown_enum, serde_test_simple!(DenyUnknownEnum, 16));
+tests!(map_enum, serde_test_simple!(MapEnum, !0));
+tests!(map_deny_unknown_enum, serde_test_simple!(MapDenyUnknownEnum, !0));
+tests!(versioned_enum, serde_test_simple!(VersionedEnum, 2));
+tests!(versioned_deny_unknown_enum, serde_test_simple!(VersionedDenyUnknownEnum, 3));
@@ -0,0 +1,27 @@
+#![cfg(feature = "smallvec")]
+
+use enumset::*;
+
+#[derive(EnumSetType, Debug)]
+pub enum SmallEnum {
+    A, B, C, D, E, F, G, H,
+}
+
+#[derive(EnumSetType, Debug)]
+pub enum LargeEnum {
+    A = 0, B = 20, C = 40, D = 60, E = 80, F = 100, G = 120,
+}
+
+#[test]
+fn to_smallvec() {
+    let set = SmallEnum::A | SmallEnum::C | SmallEnum::H;
+    let vec = set.to_smallvec();
+    assert_eq!(vec.as_slice(), &[SmallEnum::A, SmallEnum::C, SmallEnum::H]);
+    assert!(!vec.spilled());
+    assert!(!EnumSet::<SmallEnum>::all().to_smallvec().spilled());
+    assert!(EnumSet::<SmallEnum>::empty().to_smallvec().is_empty());
+
+    let large = EnumSet::<LargeEnum>::all().to_smallvec();
+    assert_eq!(large.len(), 7);
+    assert_eq!(large[6], LargeEnum::G);
+}
@@ -7,6 +7,7 @@ use proc_macro::TokenStream;
 use proc_macro2::{Literal, Span, TokenStream as SynTokenStream};
 use quote::*;
 use std::{collections::HashSet, fmt::Display};
+use syn::ext::IdentExt;
 use syn::spanned::Spanned;
 use syn::{Error, Result, *};
 
@@ -24,11 +25,15 @@ struct EnumsetAttrs {
     #[darling(default)]
     repr: Option<String>,
     serialize_as_list: bool,
+    serialize_as_map: bool,
     serialize_deny_unknown: bool,
+    serialize_versioned: bool,
     #[darling(default)]
     serialize_repr: Option<String>,
     #[darling(default)]
     crate_name: Option<String>,
+    #[darling(default)]
+    subset_of: Option<String>,
 }
 
 /// An variant in the enum set type.
@@ -50,6 +55,8 @@ struct EnumSetInfo {
     explicit_mem_repr: Option<Ident>,
     /// The numeric type to serialize the enum as.
     explicit_serde_repr: Option<Ident>,
+    /// The enum type this enum is a subset of.
+    subset_of: Option<Path>,
     /// Whether the underlying repr of the enum supports negative values.
     has_signed_repr: bool,
     /// Whether the underlying repr of the enum supports values higher than 2^32.
@@ -72,18 +79,30 @@ struct EnumSetInfo {
     no_super_impls: bool,
     /// Serialize the enum as a list.
     serialize_as_list: bool,
+    /// Serialize the enum as a map of variant names to booleans.
+    serialize_as_map: bool,
     /// Disallow unknown bits while deserializing the enum.
     serialize_deny_unknown: bool,
+    /// Serialize the enum together with a format version.
+    serialize_versioned: bool,
 }
 impl EnumSetInfo {
-    fn new(input: &DeriveInput, attrs: EnumsetAttrs) -> EnumSetInfo {
-        EnumSetInfo {
+    fn new(input: &DeriveInput, attrs: EnumsetAttrs) -> Result<EnumSetInfo> {
+        let subset_of = match attrs.subset_of {
+            Some(path) => match parse_str(&path) {
+                Ok(path) => Some(path),
+                Err(_) => error(input.span(), "`subset_of` must be a path to an enum.")?,
+            },
+            None => None,
+        };
+        Ok(EnumSetInfo {
             name: input.ident.clone(),
             crate_name: attrs.crate_name.map(|x| Ident::new(&x, Span::call_site())),
             explicit_mem_repr: attrs.repr.map(|x| Ident::new(&x, Span::call_site())),
             explicit_serde_repr: attrs
                 .serialize_repr
                 .map(|x| Ident::new(&x, Span::call_site())),
+            subset_of,
             has_signed_repr: false,
             has_large_repr: false,
             variants: Vec::new(),
@@ -94,8 +113,10 @@ impl EnumSetInfo {
             no_ops: attrs.no_ops,
             no_super_impls: attrs.no_super_impls,
             serialize_as_list: attrs.serialize_as_list,
+            serialize_as_map: attrs.serialize_as_map,
             serialize_deny_unknown: attrs.serialize_deny_unknown,
-        }
+            serialize_versioned: attrs.serialize_versioned,
+        })
     }
 
     /// Sets an explicit repr for the enumset.
@@ -206,6 +227,17 @@ impl EnumSetInfo {
             Ok(())
         }
 
+        // Check that the serialization options are compatible with each other.
+        let serialize_modes =
+            [self.serialize_as_list, self.serialize_as_map, self.serialize_versioned];
+        if serialize_modes.iter().filter(|x| **x).count() > 1 {
+            error(
+                Span::call_site(),
+                "Only one of `serialize_as_list`, `serialize_as_map` and `serialize_versioned` \
+                 may be used.",
+            )?;
+        }
+
         // Check if all bits of the bitset can fit in the serialization representation.
         if let Some(explicit_serde_repr) = &self.explicit_serde_repr {
             do_check(&explicit_serde_repr.to_string(), self.max_discrim, "serialize_repr")?;
@@ -257,6 +289,29 @@ impl EnumSetInfo {
     }
 }
 
+/// Generates the check for unknown bits in a deserialized value, if it was requested.
+#[cfg(feature = "serde")]
+fn check_unknown(
+    info: &EnumSetInfo,
+    serde: &SynTokenStream,
+    core: &SynTokenStream,
+    error: SynTokenStream,
+) -> SynTokenStream {
+    if info.serialize_deny_unknown {
+        let all_variants = Literal::u128_unsuffixed(info.all_variants());
+        quote! {
+            if value & !#all_variants != 0 {
+                use #serde::de::Error;
+                return #core::prelude::v1::Err(
+                    <#error>::custom("enumset contains unknown bits")
+                )
+            }
+        }
+    } else {
+        quote! {}
+    }
+}
+
 /// Generates the actual `EnumSetType` impl.
 fn enum_set_type_impl(info: EnumSetInfo) -> SynTokenStream {
     let name = &info.name;
@@ -375,20 +430,156 @@ fn enum_set_type_impl(info: EnumSetInfo) -> SynTokenStream {
                 de.deserialize_seq(Visitor)
             }
         }
-    } else {
-        let serialize_repr = info.serde_repr();
-        let check_unknown = if info.serialize_deny_unknown {
+    } else if info.serialize_as_map {
+        let expecting_str = format!("a map of {} variant names to booleans", name);
+        let variant_name: Vec<_> = info.variants.iter().map(|x| &x.name).collect();
+        let variant_str: Vec<_> = info
+            .variants
+            .iter()
+            .map(|x| x.name.unraw().to_string())
+            .collect();
+        let variant_count = info.variants.len();
+        let unknown_key = if info.serialize_deny_unknown {
             quote! {
-                if value & !#all_variants != 0 {
-                    use #serde::de::Error;
-                    return #core::prelude::v1::Err(
-                        D::Error::custom("enumset contains unknown bits")
-                    )
-                }
+                use #serde::de::Error;
+                return #core::prelude::v1::Err(A::Error::custom("enumset contains unknown keys"))
             }
         } else {
-            quote! {}
+            quote! {
+                map.next_value::<#serde::de::IgnoredAny>()?;
+            }
         };
+        quote! {
+            fn serialize<S: #serde::Serializer>(
+                set: #enumset::EnumSet<#name>, ser: S,
+            ) -> #core::result::Result<S::Ok, S::Error> {
+                use #serde::ser::SerializeMap;
+                let mut map = ser.serialize_map(#core::prelude::v1::Some(#variant_count))?;
+                #(map.serialize_entry(#variant_str, &set.contains(#name::#variant_name))?;)*
+                map.end()
+            }
+            fn deserialize<'de, D: #serde::Deserializer<'de>>(
+                de: D,
+            ) -> #core::result::Result<#enumset::EnumSet<#name>, D::Error> {
+                struct VariantKey(#core::option::Option<#name>);
+                impl <'de> #serde::Deserialize<'de> for VariantKey {
+                    fn deserialize<D: #serde::Deserializer<'de>>(
+                        de: D,
+                    ) -> #core::result::Result<Self, D::Error> {
+                        struct KeyVisitor;
+                        impl <'de> #serde::de::Visitor<'de> for KeyVisitor {
+                            type Value = VariantKey;
+                            fn expecting(
+                                &self, formatter: &mut #core::fmt::Formatter,
+                            ) -> #core::fmt::Result {
+                                write!(formatter, "a variant name")
+                            }
+                            fn visit_str<E>(
+                                self, value: &str,
+                            ) -> #core::result::Result<Self::Value, E> {
+                                #core::prelude::v1::Ok(VariantKey(match value {
+                                    #(#variant_str => #core::prelude::v1::Some(#name::#variant_name),)*
+                                    _ => #core::prelude::v1::None,
+                                }))
+                            }
+                        }
+                        de.deserialize_str(KeyVisitor)
+                    }
+                }
+
+                struct Visitor;
+                impl <'de> #serde::de::Visitor<'de> for Visitor {
+                    type Value = #enumset::EnumSet<#name>;
+                    fn expecting(
+                        &self, formatter: &mut #core::fmt::Formatter,
+                    ) -> #core::fmt::Result {
+                        write!(formatter, #expecting_str)
+                    }
+                    fn visit_map<A>(
+                        mut self, mut map: A,
+                    ) -> #core::result::Result<Self::Value, A::Error> where
+                        A: #serde::de::MapAccess<'de>
+                    {
+                        let mut accum = #enumset::EnumSet::<#name>::new();
+                        while let #core::prelude::v1::Some(key) = map.next_key::<VariantKey>()? {
+                            match key.0 {
+                                #core::prelude::v1::Some(variant) => {
+                                    if map.next_value::<bool>()? {
+                                        accum |= variant;
+                                    }
+                                }
+                                #core::prelude::v1::None => {
+                                    #unknown_key
+                                }
+                            }
+                        }
+                        #core::prelude::v1::Ok(accum)
+                    }
+                }
+                de.deserialize_map(Visitor)
+            }
+        }
+    } else if info.serialize_versioned {
+        let serialize_repr = info.serde_repr();
+        let check_unknown = check_unknown(&info, &serde, &core, quote!(A::Error));
+        let expecting_str = format!("a versioned set of {}", name);
+        quote! {
+            fn serialize<S: #serde::Serializer>(
+                set: #enumset::EnumSet<#name>, ser: S,
+            ) -> #core::result::Result<S::Ok, S::Error> {
+                use #serde::ser::SerializeTuple;
+                let mut tuple = ser.serialize_tuple(2)?;
+                tuple.serialize_element(&1u8)?;
+                tuple.serialize_element(&(set.__priv_repr as #serialize_repr))?;
+                tuple.end()
+            }
+            fn deserialize<'de, D: #serde::Deserializer<'de>>(
+                de: D,
+            ) -> #core::result::Result<#enumset::EnumSet<#name>, D::Error> {
+                struct Visitor;
+                impl <'de> #serde::de::Visitor<'de> for Visitor {
+                    type Value = #enumset::EnumSet<#name>;
+                    fn expecting(
+                        &self, formatter: &mut #core::fmt::Formatter,
+                    ) -> #core::fmt::Result {
+                        write!(formatter, #expecting_str)
+                    }
+                    fn visit_seq<A>(
+                        mut self, mut seq: A,
+                    ) -> #core::result::Result<Self::Value, A::Error> where
+                        A: #serde::de::SeqAccess<'de>
+                    {
+                        use #serde::de::Error;
+                        let version = match seq.next_element::<u8>()? {
+                            #core::prelude::v1::Some(version) => version,
+                            #core::prelude::v1::None =>
+                                return #core::prelude::v1::Err(A::Error::invalid_length(0, &self)),
+                        };
+                        match version {
+                            1 => {
+                                let value = match seq.next_element::<#serialize_repr>()? {
+                                    #core::prelude::v1::Some(value) => value,
+                                    #core::prelude::v1::None => return #core::prelude::v1::Err(
+                                        A::Error::invalid_length(1, &self)
+                                    ),
+                                };
+                                #check_unknown
+                                #core::prelude::v1::Ok(#enumset::EnumSet {
+                                    __priv_repr: (value & #all_variants) as #repr,
+                                })
+                            }
+                            _ => #core::prelude::v1::Err(
+                                A::Error::custom("enumset has an unknown format version")
+                            ),
+                        }
+                    }
+                }
+                de.deserialize_tuple(2, Visitor)
+            }
+        }
+    } else {
+        let serialize_repr = info.serde_repr();
+        let check_unknown = check_unknown(&info, &serde, &core, quote!(D::Error));
         quote! {
             fn serialize<S: #serde::Serializer>(
                 set: #enumset::EnumSet<#name>, ser: S,
@@ -410,6 +601,30 @@ fn enum_set_type_impl(info: EnumSetInfo) -> SynTokenStream {
     #[cfg(not(feature = "serde"))]
     let serde_ops = quote! {};
 
+    #[cfg(feature = "arbitrary")]
+    let arbitrary_impl = {
+        let arbitrary = quote!(#enumset::__internal::arbitrary);
+        let variant_name: Vec<_> = info.variants.iter().map(|x| &x.name).collect();
+        let variant_count = variant_name.len();
+        quote! {
+            impl<'a> #arbitrary::Arbitrary<'a> for #name {
+                fn arbitrary(
+                    u: &mut #arbitrary::Unstructured<'a>,
+                ) -> #arbitrary::Result<Self> {
+                    const VARIANTS: [#name; #variant_count] = [#(#name::#variant_name,)*];
+                    u.choose(&VARIANTS).map(|x| *x)
+                }
+                fn size_hint(_depth: usize) -> (usize, #core::option::Option<usize>) {
+                    // Choosing between at most 128 variants never needs more than one byte.
+                    (0, #core::option::Option::Some(1))
+                }
+            }
+        }
+    };
+
+    #[cfg(not(feature = "arbitrary"))]
+    let arbitrary_impl = quote! {};
+
     let is_uninhabited = info.variants.is_empty();
     let is_zst = info.variants.len() == 1;
     let into_impl = if is_uninhabited {
@@ -472,6 +687,28 @@ fn enum_set_type_impl(info: EnumSetInfo) -> SynTokenStream {
         }
     };
 
+    let name_impl = if is_uninhabited {
+        quote! {
+            fn enum_name(self) -> &'static str {
+                panic!(concat!(stringify!(#name), " is uninhabited."))
+            }
+        }
+    } else {
+        let variant_name: Vec<_> = info.variants.iter().map(|x| &x.name).collect();
+        let variant_str: Vec<_> = info
+            .variants
+            .iter()
+            .map(|x| x.name.unraw().to_string())
+            .collect();
+        quote! {
+            fn enum_name(self) -> &'static str {
+                match self {
+                    #(#name::#variant_name => #variant_str,)*
+                }
+            }
+        }
+    };
+
     let eq_impl = if is_uninhabited {
         quote!(panic!(concat!(stringify!(#name), " is uninhabited.")))
     } else {
@@ -515,18 +752,73 @@ fn enum_set_type_impl(info: EnumSetInfo) -> SynTokenStream {
         quote! {}
     };
 
+    let impl_subset_of = if let Some(super_ty) = &info.subset_of {
+        let variant_name: Vec<_> = info.variants.iter().map(|x| &x.name).collect();
+        let message: Vec<_> = info
+            .variants
+            .iter()
+            .map(|x| {
+                format!(
+                    "Variant `{}` must have the same discriminant in `{}` and `{}`.",
+                    x.name,
+                    name,
+                    super_ty.to_token_stream(),
+                )
+            })
+            .collect();
+        quote! {
+            impl #enumset::EnumSetSubsetOf<#super_ty> for #name { }
+            const _: () = {
+                #(assert!(
+                    #super_ty::#variant_name as u32 == #name::#variant_name as u32,
+                    #message,
+                );)*
+            };
+        }
+    } else {
+        quote! {}
+    };
+
+    // Check at compile time that every discriminant actually fits in the bitset. This should
+    // always be guaranteed by the checks in `push_variant` and `validate`, but a mismatch would
+    // cause undefined behavior, so we double check it against the discriminants rustc computes.
+    let bounds_check = {
+        let variant_name: Vec<_> = info.variants.iter().map(|x| &x.name).collect();
+        let message = format!(
+            "`#[derive(EnumSetType)]` produced a discriminant that does not fit in `{}`.",
+            repr,
+        );
+        let const_message =
+            format!("`enumset` does not support the memory layout of `{}` in `const fn`s.", name);
+        quote! {
+            const _: () = {
+                #(assert!((#name::#variant_name as u32) < <#repr>::BITS, #message);)*
+                #(assert!(
+                    #enumset::__internal::const_enum_into_u32(#name::#variant_name) ==
+                        #name::#variant_name as u32,
+                    #const_message,
+                );)*
+            };
+        }
+    };
+
     quote! {
         unsafe impl #enumset::__internal::EnumSetTypePrivate for #name {
             type Repr = #repr;
             const ALL_BITS: Self::Repr = #all_variants;
             #into_impl
+            #name_impl
             #serde_ops
         }
 
         unsafe impl #enumset::EnumSetType for #name { }
 
+        #bounds_check
+
         #impl_with_repr
+        #impl_subset_of
         #super_impls
+        #arbitrary_impl
 
         impl #name {
             /// Creates a new enumset with only this variant.
@@ -573,7 +865,7 @@ fn derive_enum_set_type_0(input: DeriveInput, attrs: EnumsetAttrs) -> Result<Tok
             "`#[derive(EnumSetType)]` cannot be used on enums with type parameters.",
         )
     } else if let Data::Enum(data) = &input.data {
-        let mut info = EnumSetInfo::new(&input, attrs);
+        let mut info = EnumSetInfo::new(&input, attrs)?;
         for attr in &input.attrs {
             if attr.path.is_ident(&Ident::new("repr", Span::call_site())) {
                 let meta: Ident = attr.parse_args()?;